@@ -0,0 +1,790 @@
+use crate::{
+    error::{IndexerError, Result},
+    source::TransactionRecord,
+};
+use borsh::{BorshDeserialize, BorshSerialize};
+use rusqlite::{params, Connection, OptionalExtension, Params, Transaction};
+use std::path::Path;
+use typing_master_program::{events::TypingEvent, state::{ContestMode, ScoringRule}};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS players (
+    pubkey          TEXT PRIMARY KEY,
+    display_name    TEXT,
+    country_code    TEXT,
+    created_at      INTEGER,
+    created_slot    INTEGER,
+    last_activity   INTEGER
+);
+
+CREATE TABLE IF NOT EXISTS contests (
+    pubkey              TEXT PRIMARY KEY,
+    creator             TEXT NOT NULL,
+    text_id             INTEGER,
+    duration            INTEGER NOT NULL,
+    mode                TEXT NOT NULL,
+    mode_param          INTEGER,
+    scoring             TEXT NOT NULL,
+    max_participants    INTEGER NOT NULL,
+    join_deadline       INTEGER,
+    scheduled_start     INTEGER,
+    status              TEXT NOT NULL,
+    created_at          INTEGER NOT NULL,
+    created_slot        INTEGER NOT NULL,
+    started_at          INTEGER,
+    ended_at            INTEGER
+);
+
+CREATE TABLE IF NOT EXISTS participants (
+    contest     TEXT NOT NULL,
+    player      TEXT NOT NULL,
+    joined_at   INTEGER NOT NULL,
+    signature   TEXT NOT NULL,
+    PRIMARY KEY (contest, player)
+);
+
+CREATE TABLE IF NOT EXISTS results (
+    contest         TEXT NOT NULL,
+    player          TEXT NOT NULL,
+    wpm             INTEGER NOT NULL,
+    accuracy        INTEGER NOT NULL,
+    time_taken      INTEGER NOT NULL,
+    score           INTEGER NOT NULL,
+    position        INTEGER,
+    submitted_at    INTEGER NOT NULL,
+    slot            INTEGER NOT NULL,
+    signature       TEXT NOT NULL,
+    PRIMARY KEY (contest, player)
+);
+
+CREATE TABLE IF NOT EXISTS practice_sessions (
+    id              INTEGER PRIMARY KEY AUTOINCREMENT,
+    player          TEXT NOT NULL,
+    text_id         INTEGER NOT NULL,
+    wpm             INTEGER NOT NULL,
+    accuracy        INTEGER NOT NULL,
+    words_typed     INTEGER NOT NULL,
+    recorded_at     INTEGER NOT NULL,
+    slot            INTEGER NOT NULL,
+    signature       TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS record_breaks (
+    id                  INTEGER PRIMARY KEY AUTOINCREMENT,
+    text_id             INTEGER NOT NULL,
+    holder              TEXT NOT NULL,
+    wpm                 INTEGER NOT NULL,
+    accuracy            INTEGER NOT NULL,
+    previous_holder     TEXT,
+    previous_wpm        INTEGER NOT NULL,
+    set_at              INTEGER NOT NULL,
+    slot                INTEGER NOT NULL,
+    signature           TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS ghosts (
+    player              TEXT NOT NULL,
+    text_id             INTEGER NOT NULL,
+    wpm                 INTEGER NOT NULL,
+    trace_hash          TEXT NOT NULL,
+    published_at        INTEGER NOT NULL,
+    slot                INTEGER NOT NULL,
+    PRIMARY KEY (player, text_id)
+);
+
+CREATE TABLE IF NOT EXISTS daily_challenges (
+    day                 INTEGER PRIMARY KEY,
+    text_id             INTEGER NOT NULL,
+    opened_at           INTEGER NOT NULL,
+    slot                INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS daily_results (
+    day                 INTEGER NOT NULL,
+    player              TEXT NOT NULL,
+    wpm                 INTEGER NOT NULL,
+    accuracy            INTEGER NOT NULL,
+    rank                INTEGER,
+    streak              INTEGER NOT NULL,
+    submitted_at        INTEGER NOT NULL,
+    slot                INTEGER NOT NULL,
+    signature           TEXT NOT NULL,
+    PRIMARY KEY (day, player)
+);
+
+CREATE TABLE IF NOT EXISTS streak_milestones (
+    player              TEXT NOT NULL,
+    streak              INTEGER NOT NULL,
+    reached_at          INTEGER NOT NULL,
+    slot                INTEGER NOT NULL,
+    signature           TEXT NOT NULL,
+    PRIMARY KEY (player, reached_at)
+);
+
+CREATE TABLE IF NOT EXISTS xp_awards (
+    id                  INTEGER PRIMARY KEY AUTOINCREMENT,
+    player              TEXT NOT NULL,
+    amount              INTEGER NOT NULL,
+    awarded_at          INTEGER NOT NULL,
+    slot                INTEGER NOT NULL,
+    signature           TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS achievements (
+    player              TEXT NOT NULL,
+    achievement         INTEGER NOT NULL,
+    unlocked_at         INTEGER NOT NULL,
+    mint                TEXT,
+    claimed_at          INTEGER,
+    slot                INTEGER NOT NULL,
+    signature           TEXT NOT NULL,
+    PRIMARY KEY (player, achievement)
+);
+
+CREATE INDEX IF NOT EXISTS record_breaks_text ON record_breaks (text_id, set_at);
+CREATE INDEX IF NOT EXISTS practice_sessions_player ON practice_sessions (player, text_id, recorded_at);
+CREATE INDEX IF NOT EXISTS results_player ON results (player);
+
+-- Updates whose target row had not been indexed yet, e.g. a contest ending
+-- in a file ingested before the one that created it.
+CREATE TABLE IF NOT EXISTS deferred_events (
+    id          INTEGER PRIMARY KEY AUTOINCREMENT,
+    signature   TEXT NOT NULL,
+    slot        INTEGER NOT NULL,
+    event       BLOB NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS processed_transactions (
+    signature   TEXT PRIMARY KEY,
+    slot        INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS sync_state (
+    id          INTEGER PRIMARY KEY CHECK (id = 0),
+    last_slot   INTEGER NOT NULL
+);
+";
+
+/// Bumped whenever `SCHEMA` changes shape; stored in `PRAGMA user_version`.
+const SCHEMA_VERSION: i64 = 1;
+
+pub struct Database {
+    conn: Connection,
+}
+
+impl Database {
+    pub fn open(path: &Path) -> Result<Self> {
+        Self::with_connection(Connection::open(path)?)
+    }
+
+    #[cfg(test)]
+    pub fn open_in_memory() -> Result<Self> {
+        Self::with_connection(Connection::open_in_memory()?)
+    }
+
+    fn with_connection(mut conn: Connection) -> Result<Self> {
+        let version: i64 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+        if version > SCHEMA_VERSION {
+            return Err(IndexerError::SchemaVersion(version));
+        }
+
+        let tx = conn.transaction()?;
+        tx.execute_batch(SCHEMA)?;
+        tx.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+        tx.commit()?;
+        Ok(Self { conn })
+    }
+
+    /// Highest slot fully applied so far, if any.
+    pub fn last_slot(&self) -> Result<Option<u64>> {
+        let slot: Option<i64> = self
+            .conn
+            .query_row("SELECT last_slot FROM sync_state WHERE id = 0", [], |row| row.get(0))
+            .optional()?;
+        Ok(slot.map(|s| s as u64))
+    }
+
+    pub fn is_processed(&self, signature: &str) -> Result<bool> {
+        let found: Option<i64> = self
+            .conn
+            .query_row(
+                "SELECT 1 FROM processed_transactions WHERE signature = ?1",
+                [signature],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(found.is_some())
+    }
+
+    /// Apply one transaction's events and advance the cursor atomically.
+    ///
+    /// Updates to rows that aren't indexed yet are deferred, and deferred
+    /// updates are retried once the transaction's own rows are in. Returns
+    /// `false` when the transaction was already indexed.
+    pub fn apply_transaction(&mut self, record: &TransactionRecord, events: &[TypingEvent]) -> Result<bool> {
+        if self.is_processed(&record.signature)? {
+            return Ok(false);
+        }
+
+        let tx = self.conn.transaction()?;
+        for event in events {
+            if has_target(&tx, event)? {
+                apply_event(&tx, record, event)?;
+            } else {
+                tx.execute(
+                    "INSERT INTO deferred_events (signature, slot, event) VALUES (?1, ?2, ?3)",
+                    params![record.signature, record.slot as i64, event.try_to_vec()?],
+                )?;
+            }
+        }
+        retry_deferred(&tx)?;
+        tx.execute(
+            "INSERT INTO processed_transactions (signature, slot) VALUES (?1, ?2)",
+            params![record.signature, record.slot as i64],
+        )?;
+        tx.execute(
+            "INSERT INTO sync_state (id, last_slot) VALUES (0, ?1)
+             ON CONFLICT (id) DO UPDATE SET last_slot = MAX(last_slot, excluded.last_slot)",
+            [record.slot as i64],
+        )?;
+        tx.commit()?;
+        Ok(true)
+    }
+
+    #[cfg(test)]
+    pub fn connection(&self) -> &Connection {
+        &self.conn
+    }
+}
+
+fn row_exists<P: Params>(tx: &Transaction, sql: &str, params: P) -> rusqlite::Result<bool> {
+    tx.query_row(sql, params, |_| Ok(())).optional().map(|found| found.is_some())
+}
+
+/// Whether the row an update-only event changes has been indexed yet.
+fn has_target(tx: &Transaction, event: &TypingEvent) -> rusqlite::Result<bool> {
+    match event {
+        TypingEvent::ContestStarted { contest, .. }
+        | TypingEvent::ContestEnded { contest, .. }
+        | TypingEvent::ContestCancelled { contest, .. }
+        | TypingEvent::ContestTextDrawn { contest, .. } => {
+            row_exists(tx, "SELECT 1 FROM contests WHERE pubkey = ?1", [contest.to_string()])
+        }
+        TypingEvent::ResultRanked { contest, player, .. } => row_exists(
+            tx,
+            "SELECT 1 FROM results WHERE contest = ?1 AND player = ?2",
+            [contest.to_string(), player.to_string()],
+        ),
+        TypingEvent::AchievementClaimed { player, achievement, .. } => row_exists(
+            tx,
+            "SELECT 1 FROM achievements WHERE player = ?1 AND achievement = ?2",
+            params![player.to_string(), achievement],
+        ),
+        _ => Ok(true),
+    }
+}
+
+/// Apply deferred updates whose rows now exist, in slot order.
+fn retry_deferred(tx: &Transaction) -> Result<()> {
+    let deferred: Vec<(i64, String, i64, Vec<u8>)> = tx
+        .prepare("SELECT id, signature, slot, event FROM deferred_events ORDER BY slot, id")?
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)))?
+        .collect::<rusqlite::Result<_>>()?;
+    for (id, signature, slot, data) in deferred {
+        let event = TypingEvent::try_from_slice(&data)?;
+        if !has_target(tx, &event)? {
+            continue;
+        }
+        let record = TransactionRecord {
+            signature,
+            slot: slot as u64,
+            block_time: None,
+            failed: false,
+            logs: Vec::new(),
+        };
+        apply_event(tx, &record, &event)?;
+        tx.execute("DELETE FROM deferred_events WHERE id = ?1", [id])?;
+    }
+    Ok(())
+}
+
+fn touch_player(tx: &Transaction, player: &str, timestamp: i64) -> rusqlite::Result<()> {
+    tx.execute(
+        "INSERT INTO players (pubkey, last_activity) VALUES (?1, ?2)
+         ON CONFLICT (pubkey) DO UPDATE SET last_activity = MAX(COALESCE(last_activity, 0), excluded.last_activity)",
+        params![player, timestamp],
+    )?;
+    Ok(())
+}
+
+/// Split a contest mode into its name and optional parameter.
+fn mode_columns(mode: &ContestMode) -> (&'static str, Option<u32>) {
+    match *mode {
+        ContestMode::FixedText => ("fixed_text", None),
+        ContestMode::Timed { seconds } => ("timed", Some(seconds)),
+        ContestMode::SuddenDeath => ("sudden_death", None),
+        ContestMode::AccuracyGate { min } => ("accuracy_gate", Some(min)),
+    }
+}
+
+fn scoring_name(scoring: &ScoringRule) -> &'static str {
+    match scoring {
+        ScoringRule::GrossWpm => "gross_wpm",
+        ScoringRule::NetWpm => "net_wpm",
+        ScoringRule::WpmAccuracySquared => "wpm_accuracy_squared",
+        ScoringRule::Accuracy => "accuracy",
+    }
+}
+
+fn apply_event(tx: &Transaction, record: &TransactionRecord, event: &TypingEvent) -> rusqlite::Result<()> {
+    let slot = record.slot as i64;
+    match event {
+        TypingEvent::PlayerInitialized { player, timestamp } => {
+            let player = player.to_string();
+            touch_player(tx, &player, *timestamp)?;
+            tx.execute(
+                "UPDATE players SET created_at = ?2, created_slot = ?3 WHERE pubkey = ?1",
+                params![player, timestamp, slot],
+            )?;
+        }
+        TypingEvent::ContestCreated {
+            contest,
+            creator,
+            text_id,
+            duration,
+            mode,
+            scoring,
+            schedule,
+            max_participants,
+            timestamp,
+        } => {
+            let (mode, mode_param) = mode_columns(mode);
+            tx.execute(
+                "INSERT OR IGNORE INTO contests
+                 (pubkey, creator, text_id, duration, mode, mode_param, scoring, max_participants,
+                  join_deadline, scheduled_start, status, created_at, created_slot)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, 'waiting', ?11, ?12)",
+                params![
+                    contest.to_string(),
+                    creator.to_string(),
+                    text_id,
+                    *duration as i64,
+                    mode,
+                    mode_param,
+                    scoring_name(scoring),
+                    max_participants,
+                    schedule.map(|s| s.join_deadline),
+                    schedule.map(|s| s.scheduled_start),
+                    timestamp,
+                    slot
+                ],
+            )?;
+        }
+        TypingEvent::ContestJoined { contest, player, timestamp } => {
+            let player = player.to_string();
+            touch_player(tx, &player, *timestamp)?;
+            tx.execute(
+                "INSERT OR IGNORE INTO participants (contest, player, joined_at, signature)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![contest.to_string(), player, timestamp, record.signature],
+            )?;
+        }
+        TypingEvent::ContestStarted { contest, timestamp } => {
+            tx.execute(
+                "UPDATE contests SET status = 'active', started_at = ?2 WHERE pubkey = ?1",
+                params![contest.to_string(), timestamp],
+            )?;
+        }
+        TypingEvent::ResultSubmitted {
+            contest,
+            player,
+            wpm,
+            accuracy,
+            time_taken,
+            score,
+            timestamp,
+        } => {
+            let player = player.to_string();
+            touch_player(tx, &player, *timestamp)?;
+            tx.execute(
+                "INSERT OR REPLACE INTO results
+                 (contest, player, wpm, accuracy, time_taken, score, submitted_at, slot, signature)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                params![
+                    contest.to_string(),
+                    player,
+                    wpm,
+                    accuracy,
+                    *time_taken as i64,
+                    *score as i64,
+                    timestamp,
+                    slot,
+                    record.signature
+                ],
+            )?;
+        }
+        TypingEvent::ContestEnded { contest, timestamp } => {
+            tx.execute(
+                "UPDATE contests SET status = 'ended', ended_at = ?2 WHERE pubkey = ?1",
+                params![contest.to_string(), timestamp],
+            )?;
+        }
+        TypingEvent::ContestCancelled { contest, timestamp } => {
+            tx.execute(
+                "UPDATE contests SET status = 'cancelled', ended_at = ?2 WHERE pubkey = ?1",
+                params![contest.to_string(), timestamp],
+            )?;
+        }
+        TypingEvent::ResultRanked { contest, player, position } => {
+            tx.execute(
+                "UPDATE results SET position = ?3 WHERE contest = ?1 AND player = ?2",
+                params![contest.to_string(), player.to_string(), position],
+            )?;
+        }
+        TypingEvent::PracticeRecorded {
+            player,
+            text_id,
+            wpm,
+            accuracy,
+            words_typed,
+            timestamp,
+        } => {
+            let player = player.to_string();
+            touch_player(tx, &player, *timestamp)?;
+            tx.execute(
+                "INSERT INTO practice_sessions
+                 (player, text_id, wpm, accuracy, words_typed, recorded_at, slot, signature)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                params![player, text_id, wpm, accuracy, words_typed, timestamp, slot, record.signature],
+            )?;
+        }
+        // Personal bests are derivable from the practice and result rows.
+        TypingEvent::PersonalBestSet { .. } => {}
+        TypingEvent::ProfileUpdated {
+            player,
+            display_name,
+            country_code,
+            timestamp,
+        } => {
+            let player = player.to_string();
+            touch_player(tx, &player, *timestamp)?;
+            let country_code = (*country_code != [0, 0]).then(|| String::from_utf8_lossy(country_code).into_owned());
+            tx.execute(
+                "UPDATE players SET display_name = ?2, country_code = ?3 WHERE pubkey = ?1",
+                params![player, display_name, country_code],
+            )?;
+        }
+        TypingEvent::TextRecordBroken {
+            text_id,
+            holder,
+            wpm,
+            accuracy,
+            previous_holder,
+            previous_wpm,
+            timestamp,
+        } => {
+            tx.execute(
+                "INSERT INTO record_breaks
+                 (text_id, holder, wpm, accuracy, previous_holder, previous_wpm, set_at, slot, signature)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                params![
+                    text_id,
+                    holder.to_string(),
+                    wpm,
+                    accuracy,
+                    previous_holder.map(|p| p.to_string()),
+                    previous_wpm,
+                    timestamp,
+                    slot,
+                    record.signature
+                ],
+            )?;
+        }
+        TypingEvent::GhostPublished {
+            player,
+            text_id,
+            wpm,
+            trace_hash,
+            timestamp,
+        } => {
+            let trace_hash: String = trace_hash.iter().map(|b| format!("{:02x}", b)).collect();
+            tx.execute(
+                "INSERT INTO ghosts (player, text_id, wpm, trace_hash, published_at, slot)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+                 ON CONFLICT(player, text_id) DO UPDATE SET
+                     wpm = excluded.wpm,
+                     trace_hash = excluded.trace_hash,
+                     published_at = excluded.published_at,
+                     slot = excluded.slot",
+                params![player.to_string(), text_id, wpm, trace_hash, timestamp, slot],
+            )?;
+        }
+        TypingEvent::DailyChallengeOpened { day, text_id, timestamp } => {
+            tx.execute(
+                "INSERT OR IGNORE INTO daily_challenges (day, text_id, opened_at, slot)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![day, text_id, timestamp, slot],
+            )?;
+        }
+        TypingEvent::DailyResultSubmitted {
+            day,
+            player,
+            wpm,
+            accuracy,
+            rank,
+            streak,
+            timestamp,
+        } => {
+            let player = player.to_string();
+            touch_player(tx, &player, *timestamp)?;
+            // Ranks are as of submission; later entries may push this one down.
+            tx.execute(
+                "INSERT OR IGNORE INTO daily_results
+                 (day, player, wpm, accuracy, rank, streak, submitted_at, slot, signature)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                params![day, player, wpm, accuracy, rank, streak, timestamp, slot, record.signature],
+            )?;
+        }
+        // A drawn contest's race starts with the draw, not the start
+        TypingEvent::ContestTextDrawn { contest, text_id, timestamp } => {
+            tx.execute(
+                "UPDATE contests SET text_id = ?2, started_at = ?3 WHERE pubkey = ?1",
+                params![contest.to_string(), text_id, timestamp],
+            )?;
+        }
+        TypingEvent::StreakMilestone { player, streak, timestamp } => {
+            tx.execute(
+                "INSERT OR IGNORE INTO streak_milestones (player, streak, reached_at, slot, signature)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![player.to_string(), streak, timestamp, slot, record.signature],
+            )?;
+        }
+        TypingEvent::AchievementUnlocked {
+            player,
+            achievement,
+            timestamp,
+        } => {
+            tx.execute(
+                "INSERT OR IGNORE INTO achievements (player, achievement, unlocked_at, slot, signature)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![player.to_string(), achievement, timestamp, slot, record.signature],
+            )?;
+        }
+        TypingEvent::AchievementClaimed {
+            player,
+            achievement,
+            mint,
+            timestamp,
+        } => {
+            tx.execute(
+                "UPDATE achievements SET mint = ?3, claimed_at = ?4 WHERE player = ?1 AND achievement = ?2",
+                params![player.to_string(), achievement, mint.to_string(), timestamp],
+            )?;
+        }
+        TypingEvent::XpAwarded { player, amount, timestamp } => {
+            tx.execute(
+                "INSERT INTO xp_awards (player, amount, awarded_at, slot, signature)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![player.to_string(), *amount as i64, timestamp, slot, record.signature],
+            )?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_program::pubkey::Pubkey;
+
+    fn record(signature: &str, slot: u64) -> TransactionRecord {
+        TransactionRecord {
+            signature: signature.to_string(),
+            slot,
+            block_time: None,
+            failed: false,
+            logs: Vec::new(),
+        }
+    }
+
+    fn count(db: &Database, table: &str) -> i64 {
+        db.connection()
+            .query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |row| row.get(0))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_contest_lifecycle() {
+        let mut db = Database::open_in_memory().unwrap();
+        let contest = Pubkey::new_unique();
+        let alice = Pubkey::new_unique();
+        let bob = Pubkey::new_unique();
+
+        db.apply_transaction(
+            &record("create", 10),
+            &[TypingEvent::ContestCreated {
+                contest,
+                creator: alice,
+                text_id: Some(3),
+                duration: 60,
+                mode: ContestMode::Timed { seconds: 30 },
+                scoring: ScoringRule::NetWpm,
+                schedule: None,
+                max_participants: 10,
+                timestamp: 100,
+            }],
+        )
+        .unwrap();
+        db.apply_transaction(
+            &record("join", 11),
+            &[
+                TypingEvent::ContestJoined { contest, player: alice, timestamp: 101 },
+                TypingEvent::ContestJoined { contest, player: bob, timestamp: 102 },
+                TypingEvent::ContestStarted { contest, timestamp: 102 },
+            ],
+        )
+        .unwrap();
+        db.apply_transaction(
+            &record("submit", 12),
+            &[
+                TypingEvent::ResultSubmitted {
+                    contest,
+                    player: bob,
+                    wpm: 80,
+                    accuracy: 97,
+                    time_taken: 45,
+                    score: 6500,
+                    timestamp: 150,
+                },
+                TypingEvent::ContestEnded { contest, timestamp: 150 },
+                TypingEvent::ResultRanked { contest, player: bob, position: 1 },
+            ],
+        )
+        .unwrap();
+
+        let (status, ended_at, mode, mode_param): (String, i64, String, Option<i64>) = db
+            .connection()
+            .query_row(
+                "SELECT status, ended_at, mode, mode_param FROM contests WHERE pubkey = ?1",
+                [contest.to_string()],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+            )
+            .unwrap();
+        assert_eq!(status, "ended");
+        assert_eq!(ended_at, 150);
+        assert_eq!((mode.as_str(), mode_param), ("timed", Some(30)));
+        let position: i64 = db
+            .connection()
+            .query_row("SELECT position FROM results WHERE player = ?1", [bob.to_string()], |row| row.get(0))
+            .unwrap();
+        assert_eq!(position, 1);
+        assert_eq!(count(&db, "participants"), 2);
+        assert_eq!(count(&db, "results"), 1);
+        assert_eq!(count(&db, "players"), 2);
+        assert_eq!(db.last_slot().unwrap(), Some(12));
+    }
+
+    #[test]
+    fn test_apply_transaction_is_idempotent() {
+        let mut db = Database::open_in_memory().unwrap();
+        let event = TypingEvent::PracticeRecorded {
+            player: Pubkey::new_unique(),
+            text_id: 1,
+            wpm: 70,
+            accuracy: 95,
+            words_typed: 50,
+            timestamp: 1,
+        };
+
+        assert!(db.apply_transaction(&record("sig", 5), std::slice::from_ref(&event)).unwrap());
+        assert!(!db.apply_transaction(&record("sig", 5), &[event]).unwrap());
+        assert_eq!(count(&db, "practice_sessions"), 1);
+    }
+
+    #[test]
+    fn test_last_slot_never_moves_backwards() {
+        let mut db = Database::open_in_memory().unwrap();
+        assert_eq!(db.last_slot().unwrap(), None);
+
+        db.apply_transaction(&record("a", 20), &[]).unwrap();
+        db.apply_transaction(&record("b", 15), &[]).unwrap();
+
+        assert_eq!(db.last_slot().unwrap(), Some(20));
+    }
+
+    #[test]
+    fn test_open_rejects_newer_schema() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.pragma_update(None, "user_version", SCHEMA_VERSION + 1).unwrap();
+
+        let result = Database::with_connection(conn);
+        assert!(matches!(result, Err(IndexerError::SchemaVersion(v)) if v == SCHEMA_VERSION + 1));
+    }
+
+    #[test]
+    fn test_updates_wait_for_rows_from_later_files() {
+        let mut db = Database::open_in_memory().unwrap();
+        let contest = Pubkey::new_unique();
+        let player = Pubkey::new_unique();
+
+        // The end of the contest is ingested before its creation and results
+        db.apply_transaction(
+            &record("end", 30),
+            &[
+                TypingEvent::ContestEnded { contest, timestamp: 300 },
+                TypingEvent::ResultRanked { contest, player, position: 1 },
+            ],
+        )
+        .unwrap();
+        db.apply_transaction(&record("start", 20), &[TypingEvent::ContestStarted { contest, timestamp: 200 }])
+            .unwrap();
+        assert_eq!(count(&db, "deferred_events"), 3);
+
+        db.apply_transaction(
+            &record("create", 10),
+            &[TypingEvent::ContestCreated {
+                contest,
+                creator: player,
+                text_id: Some(3),
+                duration: 60,
+                mode: ContestMode::FixedText,
+                scoring: ScoringRule::GrossWpm,
+                schedule: None,
+                max_participants: 10,
+                timestamp: 100,
+            }],
+        )
+        .unwrap();
+        // Started and then ended, in slot order; the ranking still waits
+        let (status, started_at, ended_at): (String, i64, i64) = db
+            .connection()
+            .query_row(
+                "SELECT status, started_at, ended_at FROM contests WHERE pubkey = ?1",
+                [contest.to_string()],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .unwrap();
+        assert_eq!((status.as_str(), started_at, ended_at), ("ended", 200, 300));
+        assert_eq!(count(&db, "deferred_events"), 1);
+
+        db.apply_transaction(
+            &record("submit", 25),
+            &[TypingEvent::ResultSubmitted {
+                contest,
+                player,
+                wpm: 80,
+                accuracy: 97,
+                time_taken: 45,
+                score: 8000,
+                timestamp: 250,
+            }],
+        )
+        .unwrap();
+        let position: i64 = db
+            .connection()
+            .query_row("SELECT position FROM results WHERE player = ?1", [player.to_string()], |row| row.get(0))
+            .unwrap();
+        assert_eq!(position, 1);
+        assert_eq!(count(&db, "deferred_events"), 0);
+    }
+}
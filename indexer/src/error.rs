@@ -0,0 +1,19 @@
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum IndexerError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Database error: {0}")]
+    Database(#[from] rusqlite::Error),
+    #[error("Invalid JSON: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("RPC request failed: {0}")]
+    Rpc(String),
+    #[error("Malformed transaction: {0}")]
+    MalformedTransaction(String),
+    #[error("Database schema version {0} is newer than this indexer supports")]
+    SchemaVersion(i64),
+}
+
+pub type Result<T> = std::result::Result<T, IndexerError>;
@@ -0,0 +1,134 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use borsh::BorshDeserialize;
+use solana_program::pubkey::Pubkey;
+use typing_master_program::events::{TypingEvent, EVENT_VERSION};
+
+const DATA_PREFIX: &str = "Program data: ";
+
+/// Events decoded from one transaction's logs.
+#[derive(Debug, Default, PartialEq)]
+pub struct ParsedEvents {
+    pub events: Vec<TypingEvent>,
+    /// `Program data:` entries from our program that did not decode, most
+    /// likely events from a program version this build does not know.
+    /// Events logged under another `EVENT_VERSION` are counted here too.
+    pub undecoded: usize,
+}
+
+/// Extract the events emitted by `program_id` from a transaction's log messages.
+///
+/// The runtime interleaves logs from every program in the transaction, so the
+/// invocation stack is tracked through the `invoke` / `success` / `failed`
+/// lines and `Program data:` entries are only decoded while our program is the
+/// one currently executing.
+pub fn parse_events(program_id: &Pubkey, logs: &[String]) -> ParsedEvents {
+    let program_id = program_id.to_string();
+    let mut stack: Vec<&str> = Vec::new();
+    let mut parsed = ParsedEvents::default();
+
+    for line in logs {
+        if let Some(data) = line.strip_prefix(DATA_PREFIX) {
+            if stack.last() == Some(&program_id.as_str()) {
+                match decode_event(data) {
+                    Some(event) => parsed.events.push(event),
+                    None => parsed.undecoded += 1,
+                }
+            }
+            continue;
+        }
+
+        let mut parts = line.split_whitespace();
+        if parts.next() != Some("Program") {
+            continue;
+        }
+        let (Some(id), Some(action)) = (parts.next(), parts.next()) else {
+            continue;
+        };
+        match action {
+            "invoke" => stack.push(id),
+            "success" | "failed:" => {
+                stack.pop();
+            }
+            _ => {}
+        }
+    }
+
+    parsed
+}
+
+fn decode_event(data: &str) -> Option<TypingEvent> {
+    // Events are logged as the layout version followed by a borsh blob of
+    // the whole enum, each in its own base64 field.
+    let mut fields = data.split_whitespace();
+    if STANDARD.decode(fields.next()?).ok()? != [EVENT_VERSION] {
+        return None;
+    }
+    let bytes = STANDARD.decode(fields.next()?).ok()?;
+    TypingEvent::try_from_slice(&bytes).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use borsh::BorshSerialize;
+
+    fn data_line(version: u8, event: &TypingEvent) -> String {
+        format!(
+            "{}{} {}",
+            DATA_PREFIX,
+            STANDARD.encode([version]),
+            STANDARD.encode(event.try_to_vec().unwrap())
+        )
+    }
+
+    #[test]
+    fn test_parse_events_only_from_program() {
+        let program_id = Pubkey::new_unique();
+        let other_program = Pubkey::new_unique();
+        let ours = TypingEvent::ContestStarted {
+            contest: Pubkey::new_unique(),
+            timestamp: 10,
+        };
+        let theirs = TypingEvent::ContestEnded {
+            contest: Pubkey::new_unique(),
+            timestamp: 20,
+        };
+
+        let logs = vec![
+            format!("Program {} invoke [1]", program_id),
+            "Program log: Instruction: Join Contest".to_string(),
+            format!("Program {} invoke [2]", other_program),
+            data_line(EVENT_VERSION, &theirs),
+            format!("Program {} success", other_program),
+            data_line(EVENT_VERSION, &ours),
+            format!("Program {} consumed 5000 of 200000 compute units", program_id),
+            format!("Program {} success", program_id),
+        ];
+
+        let parsed = parse_events(&program_id, &logs);
+        assert_eq!(parsed.events, vec![ours]);
+        assert_eq!(parsed.undecoded, 0);
+    }
+
+    #[test]
+    fn test_parse_events_counts_undecodable_data() {
+        let program_id = Pubkey::new_unique();
+        let event = TypingEvent::ContestStarted {
+            contest: Pubkey::new_unique(),
+            timestamp: 10,
+        };
+        let logs = vec![
+            format!("Program {} invoke [1]", program_id),
+            format!("{}not-base64!", DATA_PREFIX),
+            // An unversioned blob, and a well-formed event from a layout
+            // this build doesn't know
+            format!("{}{}", DATA_PREFIX, STANDARD.encode(event.try_to_vec().unwrap())),
+            data_line(EVENT_VERSION + 1, &event),
+            format!("Program {} failed: custom program error: 0x1", program_id),
+        ];
+
+        let parsed = parse_events(&program_id, &logs);
+        assert!(parsed.events.is_empty());
+        assert_eq!(parsed.undecoded, 3);
+    }
+}
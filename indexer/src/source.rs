@@ -0,0 +1,200 @@
+use crate::error::{IndexerError, Result};
+use serde_json::{json, Value};
+use solana_program::pubkey::Pubkey;
+use std::{fs, path::Path};
+
+/// The parts of a confirmed transaction the indexer cares about.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransactionRecord {
+    pub signature: String,
+    pub slot: u64,
+    pub block_time: Option<i64>,
+    pub failed: bool,
+    pub logs: Vec<String>,
+}
+
+impl TransactionRecord {
+    /// Build a record from a `getTransaction` result (`json` encoding).
+    pub fn from_json(value: &Value) -> Result<Self> {
+        let signature = value
+            .pointer("/transaction/signatures/0")
+            .and_then(Value::as_str)
+            .ok_or_else(|| IndexerError::MalformedTransaction("missing signature".into()))?
+            .to_string();
+        let slot = value
+            .get("slot")
+            .and_then(Value::as_u64)
+            .ok_or_else(|| IndexerError::MalformedTransaction(format!("{}: missing slot", signature)))?;
+        let meta = value
+            .get("meta")
+            .ok_or_else(|| IndexerError::MalformedTransaction(format!("{}: missing meta", signature)))?;
+        let logs = meta
+            .get("logMessages")
+            .and_then(Value::as_array)
+            .map(|lines| lines.iter().filter_map(Value::as_str).map(String::from).collect())
+            .unwrap_or_default();
+
+        Ok(Self {
+            signature,
+            slot,
+            block_time: value.get("blockTime").and_then(Value::as_i64),
+            failed: !meta.get("err").is_none_or(Value::is_null),
+            logs,
+        })
+    }
+}
+
+/// Load saved transactions from a file or a directory of `.json` files.
+///
+/// A file may hold a single `getTransaction` result, a raw JSON-RPC response
+/// wrapping one, or an array of either.
+pub fn load_path(path: &Path) -> Result<Vec<TransactionRecord>> {
+    let mut records = Vec::new();
+    if path.is_dir() {
+        let mut entries: Vec<_> = fs::read_dir(path)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|p| p.extension().is_some_and(|ext| ext == "json"))
+            .collect();
+        entries.sort();
+        for entry in entries {
+            records.extend(load_path(&entry)?);
+        }
+    } else {
+        let value: Value = serde_json::from_str(&fs::read_to_string(path)?)?;
+        collect_records(&value, &mut records)?;
+    }
+    Ok(records)
+}
+
+fn collect_records(value: &Value, records: &mut Vec<TransactionRecord>) -> Result<()> {
+    match value {
+        Value::Array(items) => {
+            for item in items {
+                collect_records(item, records)?;
+            }
+        }
+        Value::Object(map) if map.contains_key("result") => collect_records(&map["result"], records)?,
+        Value::Null => {}
+        _ => records.push(TransactionRecord::from_json(value)?),
+    }
+    Ok(())
+}
+
+/// Minimal JSON-RPC client for pulling program transactions from a cluster.
+pub struct RpcSource {
+    url: String,
+    program_id: Pubkey,
+}
+
+impl RpcSource {
+    const PAGE_SIZE: usize = 1000;
+
+    pub fn new(url: String, program_id: Pubkey) -> Self {
+        Self { url, program_id }
+    }
+
+    /// Fetch every successful program transaction at or after `from_slot`,
+    /// oldest first.
+    pub fn fetch_since(&self, from_slot: u64) -> Result<Vec<TransactionRecord>> {
+        let mut signatures = Vec::new();
+        let mut before: Option<String> = None;
+
+        'paging: loop {
+            let mut config = json!({ "limit": Self::PAGE_SIZE, "commitment": "confirmed" });
+            if let Some(before) = &before {
+                config["before"] = json!(before);
+            }
+            let page = self.call("getSignaturesForAddress", json!([self.program_id.to_string(), config]))?;
+            let page = page.as_array().cloned().unwrap_or_default();
+
+            for entry in &page {
+                let slot = entry.get("slot").and_then(Value::as_u64).unwrap_or(0);
+                if slot < from_slot {
+                    break 'paging;
+                }
+                if let Some(signature) = entry.get("signature").and_then(Value::as_str) {
+                    if entry.get("err").is_none_or(Value::is_null) {
+                        signatures.push(signature.to_string());
+                    }
+                    before = Some(signature.to_string());
+                }
+            }
+            if page.len() < Self::PAGE_SIZE {
+                break;
+            }
+        }
+
+        signatures.reverse();
+        let mut records = Vec::with_capacity(signatures.len());
+        for signature in signatures {
+            let config = json!({
+                "encoding": "json",
+                "commitment": "confirmed",
+                "maxSupportedTransactionVersion": 0,
+            });
+            let transaction = self.call("getTransaction", json!([signature, config]))?;
+            if !transaction.is_null() {
+                records.push(TransactionRecord::from_json(&transaction)?);
+            }
+        }
+        Ok(records)
+    }
+
+    fn call(&self, method: &str, params: Value) -> Result<Value> {
+        let request = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+        let response: Value = ureq::post(&self.url)
+            .send_json(request)
+            .map_err(|e| IndexerError::Rpc(e.to_string()))?
+            .into_json()?;
+        if let Some(error) = response.get("error") {
+            return Err(IndexerError::Rpc(error.to_string()));
+        }
+        Ok(response.get("result").cloned().unwrap_or(Value::Null))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_transaction(signature: &str, slot: u64, err: Value) -> Value {
+        json!({
+            "slot": slot,
+            "blockTime": 1_700_000_000,
+            "meta": { "err": err, "logMessages": ["Program log: hello"] },
+            "transaction": { "signatures": [signature], "message": {} },
+        })
+    }
+
+    #[test]
+    fn test_record_from_json() {
+        let record = TransactionRecord::from_json(&sample_transaction("sig1", 42, Value::Null)).unwrap();
+
+        assert_eq!(record.signature, "sig1");
+        assert_eq!(record.slot, 42);
+        assert_eq!(record.block_time, Some(1_700_000_000));
+        assert!(!record.failed);
+        assert_eq!(record.logs, vec!["Program log: hello".to_string()]);
+    }
+
+    #[test]
+    fn test_record_from_json_failed_transaction() {
+        let err = json!({ "InstructionError": [0, { "Custom": 3 }] });
+        let record = TransactionRecord::from_json(&sample_transaction("sig2", 7, err)).unwrap();
+
+        assert!(record.failed);
+    }
+
+    #[test]
+    fn test_collect_records_unwraps_rpc_responses_and_arrays() {
+        let value = json!([
+            { "jsonrpc": "2.0", "id": 1, "result": sample_transaction("a", 1, Value::Null) },
+            sample_transaction("b", 2, Value::Null),
+        ]);
+        let mut records = Vec::new();
+        collect_records(&value, &mut records).unwrap();
+
+        let signatures: Vec<_> = records.iter().map(|r| r.signature.as_str()).collect();
+        assert_eq!(signatures, vec!["a", "b"]);
+    }
+}
@@ -0,0 +1,1015 @@
+use crate::{
+    achievements,
+    error::TypingError,
+    rewards,
+    state::{
+        Config, Contest, DailyChallenge, Ghost, KeyStats, PersonalBest, Player, PlayerHistory, Profile,
+        SessionKey, TextRecord, TextRegistry, UsernameRecord,
+    },
+};
+use borsh::BorshDeserialize;
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    bpf_loader_upgradeable,
+    clock::Clock,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    system_program,
+    sysvar::{slot_hashes, Sysvar},
+};
+
+/// Declarative constraints on a single account.
+///
+/// Each check returns the account back so constraints can be chained right
+/// where the account is pulled off the instruction's account list:
+///
+/// ```ignore
+/// let player_account = next_account_info(iter)?
+///     .writable()?
+///     .owned_by(program_id)?;
+/// ```
+pub trait AccountConstraints: Sized {
+    fn signer(self) -> Result<Self, ProgramError>;
+    fn writable(self) -> Result<Self, ProgramError>;
+    fn owned_by(self, owner: &Pubkey) -> Result<Self, ProgramError>;
+    fn data_len_eq(self, len: usize) -> Result<Self, ProgramError>;
+    fn address(self, expected: &Pubkey) -> Result<Self, ProgramError>;
+    /// Require the account to be the PDA for `seeds`, returning its bump.
+    fn pda(self, seeds: &[&[u8]], program_id: &Pubkey) -> Result<(Self, u8), ProgramError>;
+}
+
+impl<'info> AccountConstraints for &AccountInfo<'info> {
+    fn signer(self) -> Result<Self, ProgramError> {
+        if !self.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        Ok(self)
+    }
+
+    fn writable(self) -> Result<Self, ProgramError> {
+        if !self.is_writable {
+            return Err(TypingError::AccountNotWritable.into());
+        }
+        Ok(self)
+    }
+
+    fn owned_by(self, owner: &Pubkey) -> Result<Self, ProgramError> {
+        if self.owner != owner {
+            return Err(TypingError::InvalidAccountOwner.into());
+        }
+        Ok(self)
+    }
+
+    fn data_len_eq(self, len: usize) -> Result<Self, ProgramError> {
+        if self.data_len() != len {
+            return Err(TypingError::InvalidAccountData.into());
+        }
+        Ok(self)
+    }
+
+    fn address(self, expected: &Pubkey) -> Result<Self, ProgramError> {
+        if self.key != expected {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        Ok(self)
+    }
+
+    fn pda(self, seeds: &[&[u8]], program_id: &Pubkey) -> Result<(Self, u8), ProgramError> {
+        let (expected, bump) = Pubkey::find_program_address(seeds, program_id);
+        if *self.key != expected {
+            return Err(TypingError::InvalidAccountData.into());
+        }
+        Ok((self, bump))
+    }
+}
+
+/// Accounts for `InitializePlayer`.
+pub struct InitializePlayerAccounts<'a, 'info> {
+    /// Pays rent; may be the player or a sponsor.
+    pub fee_payer: &'a AccountInfo<'info>,
+    pub player: &'a AccountInfo<'info>,
+    pub player_account: &'a AccountInfo<'info>,
+    pub system_program: &'a AccountInfo<'info>,
+    pub player_bump: u8,
+}
+
+impl<'a, 'info> InitializePlayerAccounts<'a, 'info> {
+    pub fn parse(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        let fee_payer = next_account_info(accounts_iter)?.signer()?.writable()?;
+        let player = next_account_info(accounts_iter)?.signer()?;
+        let (player_account, player_bump) = next_account_info(accounts_iter)?
+            .writable()?
+            .pda(&[Player::SEED, player.key.as_ref()], program_id)?;
+        let system_program = next_account_info(accounts_iter)?.address(&system_program::id())?;
+
+        Ok(Self {
+            fee_payer,
+            player,
+            player_account,
+            system_program,
+            player_bump,
+        })
+    }
+}
+
+/// Accounts for `MigratePlayer`.
+///
+/// The player data account is still in the legacy layout, so the handler
+/// checks its PDA once the owner has been read from it.
+pub struct MigratePlayerAccounts<'a, 'info> {
+    pub fee_payer: &'a AccountInfo<'info>,
+    pub player_account: &'a AccountInfo<'info>,
+    pub system_program: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> MigratePlayerAccounts<'a, 'info> {
+    pub fn parse(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        let fee_payer = next_account_info(accounts_iter)?.signer()?.writable()?;
+        let player_account = next_account_info(accounts_iter)?.writable()?.owned_by(program_id)?;
+        let system_program = next_account_info(accounts_iter)?.address(&system_program::id())?;
+
+        Ok(Self {
+            fee_payer,
+            player_account,
+            system_program,
+        })
+    }
+}
+
+/// Accounts for `MigrateContest`.
+///
+/// The contest account is still in the legacy layout, so its length is
+/// checked by the handler rather than here.
+pub struct MigrateContestAccounts<'a, 'info> {
+    pub fee_payer: &'a AccountInfo<'info>,
+    pub contest_account: &'a AccountInfo<'info>,
+    pub system_program: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> MigrateContestAccounts<'a, 'info> {
+    pub fn parse(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        let fee_payer = next_account_info(accounts_iter)?.signer()?.writable()?;
+        let contest_account = next_account_info(accounts_iter)?.writable()?.owned_by(program_id)?;
+        let system_program = next_account_info(accounts_iter)?.address(&system_program::id())?;
+
+        Ok(Self {
+            fee_payer,
+            contest_account,
+            system_program,
+        })
+    }
+}
+
+/// Accounts for `CreateContest`.
+pub struct CreateContestAccounts<'a, 'info> {
+    /// Pays rent; may be the creator or a sponsor.
+    pub fee_payer: &'a AccountInfo<'info>,
+    pub creator: &'a AccountInfo<'info>,
+    pub contest_account: &'a AccountInfo<'info>,
+    pub system_program: &'a AccountInfo<'info>,
+    /// Only passed for a contest that draws its text.
+    pub registry_account: Option<&'a AccountInfo<'info>>,
+}
+
+impl<'a, 'info> CreateContestAccounts<'a, 'info> {
+    pub fn parse(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        let fee_payer = next_account_info(accounts_iter)?.signer()?.writable()?;
+        let creator = next_account_info(accounts_iter)?.signer()?;
+        // The contest address is a fresh keypair, so it signs its own creation.
+        let contest_account = next_account_info(accounts_iter)?.signer()?.writable()?;
+        let system_program = next_account_info(accounts_iter)?.address(&system_program::id())?;
+        let registry_account = match accounts_iter.next() {
+            Some(account) => Some(account.owned_by(program_id)?.pda(&[TextRegistry::SEED], program_id)?.0),
+            None => None,
+        };
+
+        Ok(Self {
+            fee_payer,
+            creator,
+            contest_account,
+            system_program,
+            registry_account,
+        })
+    }
+}
+
+/// Accounts for `JoinContest`.
+pub struct ContestPlayerAccounts<'a, 'info> {
+    pub player: &'a AccountInfo<'info>,
+    pub contest_account: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> ContestPlayerAccounts<'a, 'info> {
+    pub fn parse(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        let player = next_account_info(accounts_iter)?.signer()?;
+        let contest_account = next_account_info(accounts_iter)?
+            .writable()?
+            .owned_by(program_id)?
+            .data_len_eq(Contest::SIZE)?;
+        // Only registered players may join; the record is updated once the
+        // contest has run (see `RecordContestOutcome`)
+        next_account_info(accounts_iter)?
+            .owned_by(program_id)?
+            .pda(&[Player::SEED, player.key.as_ref()], program_id)?;
+
+        Ok(Self {
+            player,
+            contest_account,
+        })
+    }
+}
+
+/// Accounts for `DrawContestText`.
+pub struct DrawContestTextAccounts<'a, 'info> {
+    pub contest_account: &'a AccountInfo<'info>,
+    pub registry_account: &'a AccountInfo<'info>,
+    pub slot_hashes: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> DrawContestTextAccounts<'a, 'info> {
+    pub fn parse(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        let contest_account = next_account_info(accounts_iter)?
+            .writable()?
+            .owned_by(program_id)?
+            .data_len_eq(Contest::SIZE)?;
+        let (registry_account, _) = next_account_info(accounts_iter)?
+            .owned_by(program_id)?
+            .pda(&[TextRegistry::SEED], program_id)?;
+        let slot_hashes = next_account_info(accounts_iter)?.address(&slot_hashes::id())?;
+
+        Ok(Self {
+            contest_account,
+            registry_account,
+            slot_hashes,
+        })
+    }
+
+    /// Hash of `slot` if it is still in `SlotHashes`.
+    ///
+    /// Read straight from the account data, which is a `u64` entry count
+    /// followed by `(slot, hash)` pairs newest first, rather than
+    /// deserializing all 512 entries.
+    pub fn slot_hash(&self, slot: u64) -> Result<Option<[u8; 32]>, ProgramError> {
+        let data = self.slot_hashes.data.borrow();
+        let count = data
+            .get(..8)
+            .and_then(|bytes| bytes.try_into().ok())
+            .map(u64::from_le_bytes)
+            .ok_or(TypingError::InvalidAccountData)?;
+        for entry in data[8..].chunks_exact(40).take(count as usize) {
+            let entry_slot = u64::from_le_bytes(entry[..8].try_into().unwrap());
+            if entry_slot == slot {
+                let mut hash = [0u8; 32];
+                hash.copy_from_slice(&entry[8..]);
+                return Ok(Some(hash));
+            }
+            // Newest first, so nothing further down can match
+            if entry_slot < slot {
+                break;
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// Resolve the player an `authority` is acting for.
+///
+/// The player is whoever owns `player_account`. The authority must be that
+/// owner, or a session key registered under it that is unexpired and
+/// carries `scope`.
+pub fn authorize_player(
+    program_id: &Pubkey,
+    authority: &AccountInfo,
+    player_account: &AccountInfo,
+    session_account: Option<&AccountInfo>,
+    scope: u8,
+) -> Result<Pubkey, ProgramError> {
+    let player = Player::try_from_slice(&player_account.data.borrow())?.owner;
+    player_account.pda(&[Player::SEED, player.as_ref()], program_id)?;
+    if *authority.key == player {
+        return Ok(player);
+    }
+
+    let session_account = session_account
+        .ok_or(TypingError::Unauthorized)?
+        .owned_by(program_id)?;
+    session_account.pda(&[SessionKey::SEED, player.as_ref(), authority.key.as_ref()], program_id)?;
+    let session = SessionKey::try_from_slice(&session_account.data.borrow())?;
+    if !session.allows(scope, Clock::get()?.unix_timestamp) {
+        return Err(TypingError::SessionNotAuthorized.into());
+    }
+    Ok(player)
+}
+
+/// Accounts for `SubmitResult`.
+///
+/// The personal best and text record PDAs depend on the contest's `text_id`,
+/// so the handler checks their addresses once the contest is loaded.
+pub struct SubmitResultAccounts<'a, 'info> {
+    /// The player's wallet or one of their session keys; pays rent for any
+    /// per-text accounts created along the way.
+    pub authority: &'a AccountInfo<'info>,
+    pub player: Pubkey,
+    pub contest_account: &'a AccountInfo<'info>,
+    pub player_account: &'a AccountInfo<'info>,
+    pub personal_best_account: &'a AccountInfo<'info>,
+    pub text_record_account: &'a AccountInfo<'info>,
+    pub history_account: &'a AccountInfo<'info>,
+    pub history_bump: u8,
+    pub system_program: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> SubmitResultAccounts<'a, 'info> {
+    pub fn parse(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        let authority = next_account_info(accounts_iter)?.signer()?.writable()?;
+        let contest_account = next_account_info(accounts_iter)?
+            .writable()?
+            .owned_by(program_id)?
+            .data_len_eq(Contest::SIZE)?;
+        let player_account = next_account_info(accounts_iter)?
+            .writable()?
+            .owned_by(program_id)?
+            .data_len_eq(Player::SIZE)?;
+        let personal_best_account = next_account_info(accounts_iter)?.writable()?;
+        let text_record_account = next_account_info(accounts_iter)?.writable()?;
+        let history_account = next_account_info(accounts_iter)?.writable()?;
+        let system_program = next_account_info(accounts_iter)?.address(&system_program::id())?;
+        let player = authorize_player(
+            program_id,
+            authority,
+            player_account,
+            accounts_iter.next(),
+            SessionKey::SCOPE_SUBMIT_RESULT,
+        )?;
+
+        let (history_account, history_bump) =
+            history_account.pda(&[PlayerHistory::SEED, player.as_ref()], program_id)?;
+
+        Ok(Self {
+            authority,
+            player,
+            contest_account,
+            player_account,
+            personal_best_account,
+            text_record_account,
+            history_account,
+            history_bump,
+            system_program,
+        })
+    }
+}
+
+/// Accounts for `UpdatePracticeStats`.
+pub struct PracticeAccounts<'a, 'info> {
+    /// The player's wallet or one of their session keys; pays rent for any
+    /// per-text accounts created along the way.
+    pub authority: &'a AccountInfo<'info>,
+    pub player: Pubkey,
+    pub player_account: &'a AccountInfo<'info>,
+    pub personal_best_account: &'a AccountInfo<'info>,
+    pub personal_best_bump: u8,
+    pub text_record_account: &'a AccountInfo<'info>,
+    pub text_record_bump: u8,
+    pub history_account: &'a AccountInfo<'info>,
+    pub history_bump: u8,
+    pub config_account: &'a AccountInfo<'info>,
+    pub system_program: &'a AccountInfo<'info>,
+    pub xp: Option<XpAccounts<'a, 'info>>,
+    /// The key stats PDA and its bump, when the run came with key deltas.
+    pub key_stats: Option<(&'a AccountInfo<'info>, u8)>,
+}
+
+impl<'a, 'info> PracticeAccounts<'a, 'info> {
+    pub fn parse(
+        program_id: &Pubkey,
+        accounts: &'a [AccountInfo<'info>],
+        text_id: u32,
+        has_key_stats: bool,
+    ) -> Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        let authority = next_account_info(accounts_iter)?.signer()?.writable()?;
+        let player_account = next_account_info(accounts_iter)?
+            .writable()?
+            .owned_by(program_id)?
+            .data_len_eq(Player::SIZE)?;
+        let personal_best_account = next_account_info(accounts_iter)?.writable()?;
+        let text_record_account = next_account_info(accounts_iter)?.writable()?;
+        let history_account = next_account_info(accounts_iter)?.writable()?;
+        let (config_account, _) = next_account_info(accounts_iter)?
+            .owned_by(program_id)?
+            .pda(&[Config::SEED], program_id)?;
+        let system_program = next_account_info(accounts_iter)?.address(&system_program::id())?;
+        let xp = XpAccounts::parse_optional(program_id, accounts_iter)?;
+        let key_stats_account = if has_key_stats {
+            Some(next_account_info(accounts_iter)?.writable()?)
+        } else {
+            None
+        };
+        let player = authorize_player(
+            program_id,
+            authority,
+            player_account,
+            accounts_iter.next(),
+            SessionKey::SCOPE_PRACTICE,
+        )?;
+        if let Some(xp) = &xp {
+            xp.check_recipient(&player)?;
+            config_account.writable()?;
+        }
+
+        let (personal_best_account, personal_best_bump) = personal_best_account
+            .pda(&[PersonalBest::SEED, player.as_ref(), &text_id.to_le_bytes()], program_id)?;
+        let (text_record_account, text_record_bump) = text_record_account
+            .pda(&[TextRecord::SEED, &text_id.to_le_bytes()], program_id)?;
+        let (history_account, history_bump) =
+            history_account.pda(&[PlayerHistory::SEED, player.as_ref()], program_id)?;
+        let key_stats = key_stats_account
+            .map(|account| account.pda(&[KeyStats::SEED, player.as_ref()], program_id))
+            .transpose()?;
+
+        Ok(Self {
+            authority,
+            player,
+            player_account,
+            personal_best_account,
+            personal_best_bump,
+            text_record_account,
+            text_record_bump,
+            history_account,
+            history_bump,
+            config_account,
+            system_program,
+            xp,
+            key_stats,
+        })
+    }
+}
+
+/// Accounts for minting XP to a player.
+///
+/// The player's XP token account must already exist; its address is checked
+/// with [`XpAccounts::check_recipient`] once the player is known.
+pub struct XpAccounts<'a, 'info> {
+    pub xp_mint: &'a AccountInfo<'info>,
+    pub mint_authority: &'a AccountInfo<'info>,
+    pub mint_authority_bump: u8,
+    pub token_account: &'a AccountInfo<'info>,
+    pub token_program: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> XpAccounts<'a, 'info> {
+    pub fn parse(
+        program_id: &Pubkey,
+        accounts_iter: &mut core::slice::Iter<'a, AccountInfo<'info>>,
+    ) -> Result<Self, ProgramError> {
+        let (xp_mint, _) = next_account_info(accounts_iter)?
+            .writable()?
+            .owned_by(&spl_token::id())?
+            .pda(&[rewards::XP_MINT_SEED], program_id)?;
+        let (mint_authority, mint_authority_bump) = next_account_info(accounts_iter)?
+            .pda(&[rewards::MINT_AUTHORITY_SEED], program_id)?;
+        let token_account = next_account_info(accounts_iter)?
+            .writable()?
+            .owned_by(&spl_token::id())?;
+        let token_program = next_account_info(accounts_iter)?.address(&spl_token::id())?;
+
+        Ok(Self {
+            xp_mint,
+            mint_authority,
+            mint_authority_bump,
+            token_account,
+            token_program,
+        })
+    }
+
+    /// Parse the XP accounts if the caller passed them, recognised by the XP
+    /// mint's address. Callers leave them out until the XP mint exists and
+    /// the player has an XP token account, and then earn no XP.
+    pub fn parse_optional(
+        program_id: &Pubkey,
+        accounts_iter: &mut core::slice::Iter<'a, AccountInfo<'info>>,
+    ) -> Result<Option<Self>, ProgramError> {
+        let (xp_mint, _) = Pubkey::find_program_address(&[rewards::XP_MINT_SEED], program_id);
+        if accounts_iter.as_slice().first().map(|account| account.key) != Some(&xp_mint) {
+            return Ok(None);
+        }
+        Self::parse(program_id, accounts_iter).map(Some)
+    }
+
+    /// Require the token account to be `player`'s associated XP account.
+    pub fn check_recipient(&self, player: &Pubkey) -> Result<(), ProgramError> {
+        self.token_account.pda(
+            &[player.as_ref(), spl_token::id().as_ref(), self.xp_mint.key.as_ref()],
+            &spl_associated_token_account::id(),
+        )?;
+        Ok(())
+    }
+}
+
+/// Accounts for `ReportProgress`.
+pub struct ReportProgressAccounts<'a, 'info> {
+    pub player: Pubkey,
+    pub contest_account: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> ReportProgressAccounts<'a, 'info> {
+    pub fn parse(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        let authority = next_account_info(accounts_iter)?.signer()?;
+        let contest_account = next_account_info(accounts_iter)?
+            .writable()?
+            .owned_by(program_id)?
+            .data_len_eq(Contest::SIZE)?;
+        let player_account = next_account_info(accounts_iter)?
+            .owned_by(program_id)?
+            .data_len_eq(Player::SIZE)?;
+        let player = authorize_player(
+            program_id,
+            authority,
+            player_account,
+            accounts_iter.next(),
+            SessionKey::SCOPE_REPORT_PROGRESS,
+        )?;
+
+        Ok(Self { player, contest_account })
+    }
+}
+
+/// Accounts for `PublishGhost`.
+pub struct PublishGhostAccounts<'a, 'info> {
+    pub player: &'a AccountInfo<'info>,
+    pub personal_best_account: &'a AccountInfo<'info>,
+    pub ghost_account: &'a AccountInfo<'info>,
+    pub ghost_bump: u8,
+    pub system_program: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> PublishGhostAccounts<'a, 'info> {
+    pub fn parse(
+        program_id: &Pubkey,
+        accounts: &'a [AccountInfo<'info>],
+        text_id: u32,
+    ) -> Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        let player = next_account_info(accounts_iter)?.signer()?.writable()?;
+        next_account_info(accounts_iter)?
+            .owned_by(program_id)?
+            .pda(&[Player::SEED, player.key.as_ref()], program_id)?;
+        let (personal_best_account, _) = next_account_info(accounts_iter)?
+            .owned_by(program_id)?
+            .pda(&[PersonalBest::SEED, player.key.as_ref(), &text_id.to_le_bytes()], program_id)?;
+        let (ghost_account, ghost_bump) = next_account_info(accounts_iter)?
+            .writable()?
+            .pda(&[Ghost::SEED, player.key.as_ref(), &text_id.to_le_bytes()], program_id)?;
+        let system_program = next_account_info(accounts_iter)?.address(&system_program::id())?;
+
+        Ok(Self {
+            player,
+            personal_best_account,
+            ghost_account,
+            ghost_bump,
+            system_program,
+        })
+    }
+}
+
+/// Accounts for `EndContest` and `StartScheduledContest`.
+pub struct ContestCrankAccounts<'a, 'info> {
+    pub contest_account: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> ContestCrankAccounts<'a, 'info> {
+    pub fn parse(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        let contest_account = next_account_info(accounts_iter)?
+            .writable()?
+            .owned_by(program_id)?
+            .data_len_eq(Contest::SIZE)?;
+
+        Ok(Self { contest_account })
+    }
+}
+
+/// Accounts for `RecordContestOutcome`.
+///
+/// The player PDA can only be checked once its `owner` is read, so the
+/// handler finishes validation with [`AccountConstraints::pda`].
+pub struct ContestOutcomeAccounts<'a, 'info> {
+    pub contest_account: &'a AccountInfo<'info>,
+    pub player_account: &'a AccountInfo<'info>,
+    /// The config account, for the XP caps, and the XP accounts, when the
+    /// caller passed them to have finishing XP minted.
+    pub xp: Option<(&'a AccountInfo<'info>, XpAccounts<'a, 'info>)>,
+}
+
+impl<'a, 'info> ContestOutcomeAccounts<'a, 'info> {
+    pub fn parse(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        let contest_account = next_account_info(accounts_iter)?
+            .writable()?
+            .owned_by(program_id)?
+            .data_len_eq(Contest::SIZE)?;
+        let player_account = next_account_info(accounts_iter)?
+            .writable()?
+            .owned_by(program_id)?
+            .data_len_eq(Player::SIZE)?;
+        let (config_key, _) = Pubkey::find_program_address(&[Config::SEED], program_id);
+        let xp = match accounts_iter.next() {
+            Some(config_account) if *config_account.key == config_key => {
+                let config_account = config_account.writable()?.owned_by(program_id)?;
+                Some((config_account, XpAccounts::parse(program_id, accounts_iter)?))
+            }
+            // Recording without XP uses up the outcome for good, so only the
+            // player, or one of their session keys, may choose to
+            authority => {
+                let authority = authority.ok_or(ProgramError::NotEnoughAccountKeys)?.signer()?;
+                authorize_player(
+                    program_id,
+                    authority,
+                    player_account,
+                    accounts_iter.next(),
+                    SessionKey::SCOPE_SUBMIT_RESULT,
+                )?;
+                None
+            }
+        };
+
+        Ok(Self {
+            contest_account,
+            player_account,
+            xp,
+        })
+    }
+}
+
+/// Accounts for `SetProfile`.
+///
+/// The previous username account is only present when renaming; its address
+/// comes from the stored profile, so the handler checks it.
+pub struct SetProfileAccounts<'a, 'info> {
+    pub owner: &'a AccountInfo<'info>,
+    pub profile_account: &'a AccountInfo<'info>,
+    pub profile_bump: u8,
+    pub username_account: &'a AccountInfo<'info>,
+    pub username_bump: u8,
+    pub system_program: &'a AccountInfo<'info>,
+    pub previous_username_account: Option<&'a AccountInfo<'info>>,
+}
+
+impl<'a, 'info> SetProfileAccounts<'a, 'info> {
+    pub fn parse(
+        program_id: &Pubkey,
+        accounts: &'a [AccountInfo<'info>],
+        normalized_name: &str,
+    ) -> Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        let owner = next_account_info(accounts_iter)?.signer()?.writable()?;
+        let (profile_account, profile_bump) = next_account_info(accounts_iter)?
+            .writable()?
+            .pda(&[Profile::SEED, owner.key.as_ref()], program_id)?;
+        let (username_account, username_bump) = next_account_info(accounts_iter)?
+            .writable()?
+            .pda(&[UsernameRecord::SEED, normalized_name.as_bytes()], program_id)?;
+        let system_program = next_account_info(accounts_iter)?.address(&system_program::id())?;
+        let previous_username_account = accounts_iter
+            .next()
+            .map(|account| account.writable())
+            .transpose()?;
+
+        Ok(Self {
+            owner,
+            profile_account,
+            profile_bump,
+            username_account,
+            username_bump,
+            system_program,
+            previous_username_account,
+        })
+    }
+}
+
+/// Accounts for `RegisterSessionKey`.
+pub struct RegisterSessionKeyAccounts<'a, 'info> {
+    pub owner: &'a AccountInfo<'info>,
+    pub session_account: &'a AccountInfo<'info>,
+    pub session_bump: u8,
+    pub system_program: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> RegisterSessionKeyAccounts<'a, 'info> {
+    pub fn parse(
+        program_id: &Pubkey,
+        accounts: &'a [AccountInfo<'info>],
+        session_key: &Pubkey,
+    ) -> Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        let owner = next_account_info(accounts_iter)?.signer()?.writable()?;
+        next_account_info(accounts_iter)?
+            .owned_by(program_id)?
+            .pda(&[Player::SEED, owner.key.as_ref()], program_id)?;
+        let (session_account, session_bump) = next_account_info(accounts_iter)?
+            .writable()?
+            .pda(&[SessionKey::SEED, owner.key.as_ref(), session_key.as_ref()], program_id)?;
+        let system_program = next_account_info(accounts_iter)?.address(&system_program::id())?;
+
+        Ok(Self {
+            owner,
+            session_account,
+            session_bump,
+            system_program,
+        })
+    }
+}
+
+/// Accounts for `RevokeSessionKey`.
+pub struct RevokeSessionKeyAccounts<'a, 'info> {
+    pub owner: &'a AccountInfo<'info>,
+    pub session_account: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> RevokeSessionKeyAccounts<'a, 'info> {
+    pub fn parse(
+        program_id: &Pubkey,
+        accounts: &'a [AccountInfo<'info>],
+        session_key: &Pubkey,
+    ) -> Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        let owner = next_account_info(accounts_iter)?.signer()?.writable()?;
+        let (session_account, _) = next_account_info(accounts_iter)?
+            .writable()?
+            .owned_by(program_id)?
+            .pda(&[SessionKey::SEED, owner.key.as_ref(), session_key.as_ref()], program_id)?;
+
+        Ok(Self { owner, session_account })
+    }
+}
+
+/// Accounts for `InitializeConfig`.
+pub struct InitializeConfigAccounts<'a, 'info> {
+    pub admin: &'a AccountInfo<'info>,
+    pub config_account: &'a AccountInfo<'info>,
+    pub config_bump: u8,
+    pub system_program: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> InitializeConfigAccounts<'a, 'info> {
+    pub fn parse(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        let admin = next_account_info(accounts_iter)?.signer()?.writable()?;
+        let (config_account, config_bump) = next_account_info(accounts_iter)?
+            .writable()?
+            .pda(&[Config::SEED], program_id)?;
+        let (program_data, _) = next_account_info(accounts_iter)?
+            .owned_by(&bpf_loader_upgradeable::id())?
+            .pda(&[program_id.as_ref()], &bpf_loader_upgradeable::id())?;
+        let system_program = next_account_info(accounts_iter)?.address(&system_program::id())?;
+
+        // Only whoever deployed the program may claim the config, so it
+        // can't be front-run after deployment.
+        if upgrade_authority(program_data)? != Some(*admin.key) {
+            return Err(TypingError::Unauthorized.into());
+        }
+
+        Ok(Self {
+            admin,
+            config_account,
+            config_bump,
+            system_program,
+        })
+    }
+}
+
+/// Read the upgrade authority out of a `ProgramData` account.
+fn upgrade_authority(program_data: &AccountInfo) -> Result<Option<Pubkey>, ProgramError> {
+    // bincode layout: u32 variant tag (3), u64 slot, Option<Pubkey>.
+    let data = program_data.data.borrow();
+    if data.len() < bpf_loader_upgradeable::UpgradeableLoaderState::size_of_programdata_metadata()
+        || data[..4] != 3u32.to_le_bytes()
+    {
+        return Err(TypingError::InvalidAccountData.into());
+    }
+    Ok(match data[12] {
+        0 => None,
+        _ => Some(Pubkey::try_from(&data[13..45]).map_err(|_| TypingError::InvalidAccountData)?),
+    })
+}
+
+/// Accounts for `UpdateConfig`.
+pub struct UpdateConfigAccounts<'a, 'info> {
+    pub admin: &'a AccountInfo<'info>,
+    pub config_account: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> UpdateConfigAccounts<'a, 'info> {
+    pub fn parse(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        let admin = next_account_info(accounts_iter)?.signer()?;
+        let (config_account, _) = next_account_info(accounts_iter)?
+            .writable()?
+            .owned_by(program_id)?
+            .pda(&[Config::SEED], program_id)?;
+
+        Ok(Self { admin, config_account })
+    }
+}
+
+/// Accounts for `RegisterText` and `UnregisterText`.
+///
+/// The admin is checked against the config in the handler.
+pub struct TextRegistryAccounts<'a, 'info> {
+    pub admin: &'a AccountInfo<'info>,
+    pub config_account: &'a AccountInfo<'info>,
+    pub registry_account: &'a AccountInfo<'info>,
+    pub registry_bump: u8,
+    pub system_program: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> TextRegistryAccounts<'a, 'info> {
+    pub fn parse(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        let admin = next_account_info(accounts_iter)?.signer()?.writable()?;
+        let (config_account, _) = next_account_info(accounts_iter)?
+            .owned_by(program_id)?
+            .pda(&[Config::SEED], program_id)?;
+        let (registry_account, registry_bump) = next_account_info(accounts_iter)?
+            .writable()?
+            .pda(&[TextRegistry::SEED], program_id)?;
+        let system_program = next_account_info(accounts_iter)?.address(&system_program::id())?;
+
+        Ok(Self {
+            admin,
+            config_account,
+            registry_account,
+            registry_bump,
+            system_program,
+        })
+    }
+}
+
+/// Accounts for `OpenDailyChallenge`.
+pub struct OpenDailyChallengeAccounts<'a, 'info> {
+    pub payer: &'a AccountInfo<'info>,
+    pub registry_account: &'a AccountInfo<'info>,
+    pub daily_account: &'a AccountInfo<'info>,
+    pub daily_bump: u8,
+    pub system_program: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> OpenDailyChallengeAccounts<'a, 'info> {
+    pub fn parse(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>], day: i64) -> Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        let payer = next_account_info(accounts_iter)?.signer()?.writable()?;
+        let (registry_account, _) = next_account_info(accounts_iter)?
+            .owned_by(program_id)?
+            .pda(&[TextRegistry::SEED], program_id)?;
+        let (daily_account, daily_bump) = next_account_info(accounts_iter)?
+            .writable()?
+            .pda(&[DailyChallenge::SEED, &day.to_le_bytes()], program_id)?;
+        let system_program = next_account_info(accounts_iter)?.address(&system_program::id())?;
+
+        Ok(Self {
+            payer,
+            registry_account,
+            daily_account,
+            daily_bump,
+            system_program,
+        })
+    }
+}
+
+/// Accounts for `SubmitDailyResult`.
+pub struct SubmitDailyResultAccounts<'a, 'info> {
+    pub player: Pubkey,
+    pub daily_account: &'a AccountInfo<'info>,
+    pub player_account: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> SubmitDailyResultAccounts<'a, 'info> {
+    pub fn parse(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>], day: i64) -> Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        let authority = next_account_info(accounts_iter)?.signer()?;
+        let (daily_account, _) = next_account_info(accounts_iter)?
+            .writable()?
+            .owned_by(program_id)?
+            .pda(&[DailyChallenge::SEED, &day.to_le_bytes()], program_id)?;
+        let player_account = next_account_info(accounts_iter)?
+            .writable()?
+            .owned_by(program_id)?
+            .data_len_eq(Player::SIZE)?;
+        let player = authorize_player(
+            program_id,
+            authority,
+            player_account,
+            accounts_iter.next(),
+            SessionKey::SCOPE_SUBMIT_RESULT,
+        )?;
+
+        Ok(Self {
+            player,
+            daily_account,
+            player_account,
+        })
+    }
+}
+
+/// Accounts for `ClaimAchievement`.
+pub struct ClaimAchievementAccounts<'a, 'info> {
+    pub player: &'a AccountInfo<'info>,
+    pub player_account: &'a AccountInfo<'info>,
+    pub mint_account: &'a AccountInfo<'info>,
+    pub mint_bump: u8,
+    pub mint_authority: &'a AccountInfo<'info>,
+    pub mint_authority_bump: u8,
+    pub token_account: &'a AccountInfo<'info>,
+    pub system_program: &'a AccountInfo<'info>,
+    pub token_program: &'a AccountInfo<'info>,
+    pub associated_token_program: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> ClaimAchievementAccounts<'a, 'info> {
+    pub fn parse(
+        program_id: &Pubkey,
+        accounts: &'a [AccountInfo<'info>],
+        achievement: u8,
+    ) -> Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        let player = next_account_info(accounts_iter)?.signer()?.writable()?;
+        let (player_account, _) = next_account_info(accounts_iter)?
+            .writable()?
+            .owned_by(program_id)?
+            .pda(&[Player::SEED, player.key.as_ref()], program_id)?;
+        let (mint_account, mint_bump) = next_account_info(accounts_iter)?
+            .writable()?
+            .pda(&[achievements::MINT_SEED, player.key.as_ref(), &[achievement]], program_id)?;
+        let (mint_authority, mint_authority_bump) = next_account_info(accounts_iter)?
+            .pda(&[rewards::MINT_AUTHORITY_SEED], program_id)?;
+        let (token_account, _) = next_account_info(accounts_iter)?.writable()?.pda(
+            &[player.key.as_ref(), spl_token::id().as_ref(), mint_account.key.as_ref()],
+            &spl_associated_token_account::id(),
+        )?;
+        let system_program = next_account_info(accounts_iter)?.address(&system_program::id())?;
+        let token_program = next_account_info(accounts_iter)?.address(&spl_token::id())?;
+        let associated_token_program =
+            next_account_info(accounts_iter)?.address(&spl_associated_token_account::id())?;
+
+        Ok(Self {
+            player,
+            player_account,
+            mint_account,
+            mint_bump,
+            mint_authority,
+            mint_authority_bump,
+            token_account,
+            system_program,
+            token_program,
+            associated_token_program,
+        })
+    }
+}
+
+/// Accounts for `InitializeXpMint`.
+///
+/// The admin is checked against the config in the handler.
+pub struct InitializeXpMintAccounts<'a, 'info> {
+    pub admin: &'a AccountInfo<'info>,
+    pub config_account: &'a AccountInfo<'info>,
+    pub xp_mint: &'a AccountInfo<'info>,
+    pub xp_mint_bump: u8,
+    pub mint_authority: &'a AccountInfo<'info>,
+    pub system_program: &'a AccountInfo<'info>,
+    pub token_program: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> InitializeXpMintAccounts<'a, 'info> {
+    pub fn parse(program_id: &Pubkey, accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        let admin = next_account_info(accounts_iter)?.signer()?.writable()?;
+        let (config_account, _) = next_account_info(accounts_iter)?
+            .owned_by(program_id)?
+            .pda(&[Config::SEED], program_id)?;
+        let (xp_mint, xp_mint_bump) = next_account_info(accounts_iter)?
+            .writable()?
+            .pda(&[rewards::XP_MINT_SEED], program_id)?;
+        let (mint_authority, _) = next_account_info(accounts_iter)?
+            .pda(&[rewards::MINT_AUTHORITY_SEED], program_id)?;
+        let system_program = next_account_info(accounts_iter)?.address(&system_program::id())?;
+        let token_program = next_account_info(accounts_iter)?.address(&spl_token::id())?;
+
+        Ok(Self {
+            admin,
+            config_account,
+            xp_mint,
+            xp_mint_bump,
+            mint_authority,
+            system_program,
+            token_program,
+        })
+    }
+}
@@ -0,0 +1,47 @@
+use crate::state::Player;
+
+/// Seed prefix of an achievement's mint, at `[b"achievement_mint", player, [id]]`.
+pub const MINT_SEED: &[u8] = b"achievement_mint";
+
+/// A badge unlocked once a player's stats meet its criteria.
+pub struct Achievement {
+    /// Bit index in `Player::achievements`. Never reuse or renumber one.
+    pub id: u8,
+    pub name: &'static str,
+    pub unlocked: fn(&Player) -> bool,
+}
+
+/// Every achievement, checked after each stats update. Add new ones to the
+/// end with the next free id; the handlers pick them up unchanged.
+pub const ACHIEVEMENTS: &[Achievement] = &[
+    Achievement {
+        id: 0,
+        name: "Century",
+        unlocked: |player| player.best_wpm >= 100,
+    },
+    Achievement {
+        id: 1,
+        name: "Thousand Tests",
+        unlocked: |player| player.total_tests >= 1000,
+    },
+    Achievement {
+        id: 2,
+        name: "Flawless",
+        unlocked: |player| player.best_accuracy >= 100,
+    },
+    Achievement {
+        id: 3,
+        name: "Champion",
+        unlocked: |player| player.contest_record.wins >= 1,
+    },
+    Achievement {
+        id: 4,
+        name: "Thirty Day Streak",
+        unlocked: |player| player.longest_activity_streak >= 30,
+    },
+];
+
+/// Look up an achievement by id.
+pub fn find(id: u8) -> Option<&'static Achievement> {
+    ACHIEVEMENTS.iter().find(|achievement| achievement.id == id)
+}
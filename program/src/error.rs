@@ -23,6 +23,70 @@ pub enum TypingError {
     InvalidAccountData,
     #[error("Unauthorized")]
     Unauthorized,
+    #[error("Account must be writable")]
+    AccountNotWritable,
+    #[error("Account is not owned by the expected program")]
+    InvalidAccountOwner,
+    #[error("Arithmetic overflow")]
+    ArithmeticOverflow,
+    #[error("Contest has not ended")]
+    ContestNotEnded,
+    #[error("Contest is still running")]
+    ContestStillRunning,
+    #[error("Contest outcome already recorded")]
+    OutcomeAlreadyRecorded,
+    #[error("Result failed verification")]
+    ImplausibleResult,
+    #[error("Username must be 3-20 letters, digits or underscores")]
+    InvalidUsername,
+    #[error("Username is already taken")]
+    UsernameTaken,
+    #[error("Invalid profile field")]
+    InvalidProfile,
+    #[error("Invalid session key parameters")]
+    InvalidSessionKey,
+    #[error("Session key expired or not allowed for this instruction")]
+    SessionNotAuthorized,
+    #[error("Practice submitted too soon or daily practice limit reached")]
+    PracticeRateLimited,
+    #[error("Invalid program configuration")]
+    InvalidConfig,
+    #[error("Progress update limit reached for this race")]
+    ProgressLimitReached,
+    #[error("Ghost trace is malformed or doesn't match the personal best")]
+    InvalidGhost,
+    #[error("Invalid contest mode")]
+    InvalidContestMode,
+    #[error("Invalid or missing contest schedule")]
+    InvalidSchedule,
+    #[error("Join deadline has passed")]
+    JoinDeadlinePassed,
+    #[error("Scheduled start time not reached")]
+    ScheduledStartNotReached,
+    #[error("Contest has already started")]
+    ContestAlreadyStarted,
+    #[error("Text is not registered")]
+    TextNotRegistered,
+    #[error("Text registry is full")]
+    TextRegistryFull,
+    #[error("Daily challenge already attempted today")]
+    DailyAttemptUsed,
+    #[error("Daily challenge is not for today")]
+    InvalidDay,
+    #[error("Achievement has not been unlocked")]
+    AchievementLocked,
+    #[error("Achievement has already been claimed")]
+    AchievementAlreadyClaimed,
+    #[error("Key stats delta is malformed or does not match the run")]
+    InvalidKeyStats,
+    #[error("Player account is already on the current layout")]
+    PlayerAlreadyMigrated,
+    #[error("Ghost trace was not committed with the personal best run")]
+    GhostNotCommitted,
+    #[error("Contest account is already on the current layout")]
+    ContestAlreadyMigrated,
+    #[error("Contest text can't be drawn until its draw slot has passed")]
+    DrawNotReady,
 }
 
 impl From<TypingError> for ProgramError {
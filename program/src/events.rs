@@ -0,0 +1,151 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use crate::state::{ContestMode, ContestSchedule, ScoringRule};
+use solana_program::{log::sol_log_data, pubkey::Pubkey};
+
+/// Layout version of `TypingEvent`, logged ahead of every event. Bump it
+/// whenever an existing variant's fields change.
+pub const EVENT_VERSION: u8 = 1;
+
+/// Structured events written to the transaction log with `sol_log_data`.
+///
+/// Each event shows up as a single `Program data: <base64> <base64>` line
+/// holding `EVENT_VERSION` and the borsh encoding of the variant, which
+/// off-chain indexers decode with this same type. Variants are only ever
+/// appended so their discriminants stay put.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub enum TypingEvent {
+    PlayerInitialized {
+        player: Pubkey,
+        timestamp: i64,
+    },
+    ContestCreated {
+        contest: Pubkey,
+        creator: Pubkey,
+        /// `None` until `ContestTextDrawn`.
+        text_id: Option<u32>,
+        duration: u64,
+        mode: ContestMode,
+        scoring: ScoringRule,
+        schedule: Option<ContestSchedule>,
+        max_participants: u8,
+        timestamp: i64,
+    },
+    ContestJoined {
+        contest: Pubkey,
+        player: Pubkey,
+        timestamp: i64,
+    },
+    ContestStarted {
+        contest: Pubkey,
+        timestamp: i64,
+    },
+    ResultSubmitted {
+        contest: Pubkey,
+        player: Pubkey,
+        wpm: u32,
+        accuracy: u32,
+        time_taken: u64,
+        score: u64,
+        timestamp: i64,
+    },
+    ContestEnded {
+        contest: Pubkey,
+        timestamp: i64,
+    },
+    PracticeRecorded {
+        player: Pubkey,
+        text_id: u32,
+        wpm: u32,
+        accuracy: u32,
+        words_typed: u32,
+        timestamp: i64,
+    },
+    ResultRanked {
+        contest: Pubkey,
+        player: Pubkey,
+        position: u32,
+    },
+    PersonalBestSet {
+        player: Pubkey,
+        text_id: u32,
+        wpm: u32,
+        accuracy: u32,
+        timestamp: i64,
+    },
+    TextRecordBroken {
+        text_id: u32,
+        holder: Pubkey,
+        wpm: u32,
+        accuracy: u32,
+        previous_holder: Option<Pubkey>,
+        previous_wpm: u32,
+        timestamp: i64,
+    },
+    ProfileUpdated {
+        player: Pubkey,
+        display_name: String,
+        country_code: [u8; 2],
+        timestamp: i64,
+    },
+    GhostPublished {
+        player: Pubkey,
+        text_id: u32,
+        wpm: u32,
+        trace_hash: [u8; 32],
+        timestamp: i64,
+    },
+    ContestCancelled {
+        contest: Pubkey,
+        timestamp: i64,
+    },
+    DailyChallengeOpened {
+        day: i64,
+        text_id: u32,
+        timestamp: i64,
+    },
+    DailyResultSubmitted {
+        day: i64,
+        player: Pubkey,
+        wpm: u32,
+        accuracy: u32,
+        /// Leaderboard position, if the run made the top of the day.
+        rank: Option<u8>,
+        streak: u32,
+        timestamp: i64,
+    },
+    /// The race clock of a contest without a fixed text starts here.
+    ContestTextDrawn {
+        contest: Pubkey,
+        text_id: u32,
+        timestamp: i64,
+    },
+    StreakMilestone {
+        player: Pubkey,
+        streak: u32,
+        timestamp: i64,
+    },
+    AchievementUnlocked {
+        player: Pubkey,
+        achievement: u8,
+        timestamp: i64,
+    },
+    AchievementClaimed {
+        player: Pubkey,
+        achievement: u8,
+        mint: Pubkey,
+        timestamp: i64,
+    },
+    XpAwarded {
+        player: Pubkey,
+        amount: u64,
+        timestamp: i64,
+    },
+}
+
+impl TypingEvent {
+    pub fn emit(&self) {
+        if let Ok(data) = self.try_to_vec() {
+            sol_log_data(&[&[EVENT_VERSION], &data]);
+        }
+    }
+}
@@ -1,52 +1,287 @@
 use borsh::{BorshDeserialize, BorshSerialize};
-use solana_program::program_error::ProgramError;
+use crate::state::{ConfigParams, ContestMode, ContestSchedule, KeyDelta, ScoringRule, TypingStats};
+use solana_program::{program_error::ProgramError, pubkey::Pubkey};
 
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
 pub enum TypingInstruction {
     /// Initialize a new player account
     /// Accounts:
+    /// - [signer, writable] Fee payer (the player, or a sponsor covering rent)
     /// - [signer] Player account
-    /// - [writable] Player data account (PDA)
+    /// - [writable] Player data account (PDA of the player, not the fee payer)
     /// - [] System program
     InitializePlayer,
 
     /// Create a new typing contest
     /// Accounts:
+    /// - [signer, writable] Fee payer (the creator, or a sponsor covering rent)
     /// - [signer] Creator account
-    /// - [writable] Contest data account (PDA)
+    /// - [signer, writable] Contest data account (new keypair)
     /// - [] System program
+    /// - [] Text registry account (PDA, only when `text_id` is `None`; must hold a text)
     CreateContest {
-        text_id: u32,
+        /// `None` draws the text from the registry once the contest has
+        /// started (see `DrawContestText`), so players can't rehearse it.
+        text_id: Option<u32>,
         duration: u64,
+        mode: ContestMode,
+        scoring: ScoringRule,
+        /// Start at a set time instead of when a second player joins.
+        schedule: Option<ContestSchedule>,
     },
 
     /// Join a typing contest
     /// Accounts:
     /// - [signer] Player account
     /// - [writable] Contest data account
-    /// - [writable] Player data account
+    /// - [] Player data account (PDA)
     JoinContest,
 
-    /// Submit typing results
+    /// Submit typing results; WPM, accuracy and time taken are derived from
+    /// `stats`. `trace_hash` commits the run's ghost trace (see
+    /// `PublishGhost`) in case it sets a personal best.
     /// Accounts:
-    /// - [signer] Player account
+    /// - [signer, writable] Player account, or a session key scoped for submissions
     /// - [writable] Contest data account
-    /// - [writable] Player data account
+    /// - [writable] Player data account (PDA)
+    /// - [writable] Personal best account for the contest text (PDA, created if missing)
+    /// - [writable] Text record account for the contest text (PDA, created if missing)
+    /// - [writable] Player history account (PDA, created if missing)
+    /// - [] System program
+    /// - [] Session key account (PDA, only when signing with a session key)
     SubmitResult {
-        wpm: u32,
-        accuracy: u32,
-        time_taken: u64,
+        stats: TypingStats,
+        trace_hash: Option<[u8; 32]>,
     },
 
-    /// Update player statistics after practice
+    /// Update player statistics after practice, derived from `stats`, and
+    /// fold `key_deltas` into the player's per-key stats if any are given.
+    /// `trace_hash` commits the run's ghost trace as in `SubmitResult`.
     /// Accounts:
-    /// - [signer] Player account
-    /// - [writable] Player data account
+    /// - [signer, writable] Player account, or a session key scoped for practice
+    /// - [writable] Player data account (PDA)
+    /// - [writable] Personal best account for `text_id` (PDA, created if missing)
+    /// - [writable] Text record account for `text_id` (PDA, created if missing)
+    /// - [writable] Player history account (PDA, created if missing)
+    /// - [] Config account (PDA; writable to earn XP)
+    /// - [] System program
+    /// - [writable] XP mint account (PDA, only to earn XP; the rest of the group likewise)
+    /// - [] Mint authority account (PDA)
+    /// - [writable] Player's associated token account for the XP mint (must exist)
+    /// - [] SPL Token program
+    /// - [writable] Key stats account (PDA, created if missing; only when `key_deltas` is non-empty)
+    /// - [] Session key account (PDA, only when signing with a session key)
     UpdatePracticeStats {
-        wpm: u32,
-        accuracy: u32,
-        words_typed: u32,
+        stats: TypingStats,
+        text_id: u32,
+        key_deltas: Vec<KeyDelta>,
+        trace_hash: Option<[u8; 32]>,
+    },
+
+    /// End an active contest whose duration has elapsed, marking anyone who
+    /// has not submitted as DNF. Callable by anyone.
+    /// Accounts:
+    /// - [writable] Contest data account
+    EndContest,
+
+    /// Start a scheduled contest once `scheduled_start` has passed, or
+    /// cancel it if fewer than `Contest::MIN_PARTICIPANTS` joined. Callable
+    /// by anyone.
+    /// Accounts:
+    /// - [writable] Contest data account
+    StartScheduledContest,
+
+    /// Fold a finished contest into a participant's contest record and
+    /// award their finishing XP. Callable by anyone with the XP accounts,
+    /// once per participant; only the participant may record it without XP.
+    /// Accounts:
+    /// - [writable] Contest data account
+    /// - [writable] Player data account (PDA) of the participant
+    ///
+    /// then either, to earn XP:
+    /// - [writable] Config account (PDA)
+    /// - [writable] XP mint account (PDA)
+    /// - [] Mint authority account (PDA)
+    /// - [writable] Participant's associated token account for the XP mint (must exist)
+    /// - [] SPL Token program
+    ///
+    /// or, without XP:
+    /// - [signer] The participant, or a session key scoped for submissions
+    /// - [] Session key account (PDA, only when signing with a session key)
+    RecordContestOutcome,
+
+    /// Create or update the signer's public profile, claiming the normalized
+    /// form of `display_name` in the username registry. Renaming releases
+    /// the previous name and refunds its rent to the owner.
+    /// Accounts:
+    /// - [signer, writable] Owner account
+    /// - [writable] Profile account (PDA)
+    /// - [writable] Username account for the new name (PDA)
+    /// - [] System program
+    /// - [writable] Username account for the previous name (PDA, only when renaming)
+    SetProfile {
+        display_name: String,
+        avatar_uri: String,
+        country_code: [u8; 2],
+    },
+
+    /// Authorize an ephemeral key to sign gameplay instructions for the
+    /// owner until `expires_at`. `scope` is a set of `SessionKey::SCOPE_*`
+    /// flags; re-registering the same key replaces its expiry and scope.
+    /// Accounts:
+    /// - [signer, writable] Owner account
+    /// - [] Player data account (PDA)
+    /// - [writable] Session key account (PDA)
+    /// - [] System program
+    RegisterSessionKey {
+        session_key: Pubkey,
+        expires_at: i64,
+        scope: u8,
+    },
+
+    /// Revoke a session key and refund its rent to the owner.
+    /// Accounts:
+    /// - [signer, writable] Owner account
+    /// - [writable] Session key account (PDA)
+    RevokeSessionKey {
+        session_key: Pubkey,
+    },
+
+    /// Create the program config. Only the program's upgrade authority may
+    /// do this, and it becomes the config admin.
+    /// Accounts:
+    /// - [signer, writable] Upgrade authority
+    /// - [writable] Config account (PDA)
+    /// - [] Program data account of this program
+    /// - [] System program
+    InitializeConfig {
+        params: ConfigParams,
+    },
+
+    /// Replace the tunable config parameters.
+    /// Accounts:
+    /// - [signer] Config admin
+    /// - [writable] Config account (PDA)
+    UpdateConfig {
+        params: ConfigParams,
+    },
+
+    /// Post a live checkpoint while racing, limited to
+    /// `Contest::MAX_PROGRESS_UPDATES` per participant.
+    /// Accounts:
+    /// - [signer] Player account, or a session key scoped for progress reports
+    /// - [writable] Contest data account
+    /// - [] Player data account (PDA)
+    /// - [] Session key account (PDA, only when signing with a session key)
+    ReportProgress {
+        chars_completed: u16,
+        wpm: u16,
     },
+
+    /// Publish a replay of the personal best on `text_id`. `samples` holds
+    /// cumulative characters after each `interval_ms`; its hash must match
+    /// the `trace_hash` submitted with the best run.
+    /// Accounts:
+    /// - [signer, writable] Player account
+    /// - [] Player data account (PDA)
+    /// - [] Personal best account for `text_id` (PDA)
+    /// - [writable] Ghost account for `text_id` (PDA, created if missing)
+    /// - [] System program
+    PublishGhost {
+        text_id: u32,
+        interval_ms: u16,
+        samples: Vec<u16>,
+    },
+
+    /// Add a text to the pool automatic selection draws from.
+    /// Accounts:
+    /// - [signer, writable] Config admin
+    /// - [] Config account (PDA)
+    /// - [writable] Text registry account (PDA, created if missing)
+    /// - [] System program
+    RegisterText {
+        text_id: u32,
+    },
+
+    /// Remove a text from the selection pool.
+    /// Accounts: as `RegisterText`.
+    UnregisterText {
+        text_id: u32,
+    },
+
+    /// Create today's daily challenge, picking its text from the registry.
+    /// Callable by anyone; `day` is the current UTC day number.
+    /// Accounts:
+    /// - [signer, writable] Payer
+    /// - [] Text registry account (PDA)
+    /// - [writable] Daily challenge account for `day` (PDA)
+    /// - [] System program
+    OpenDailyChallenge {
+        day: i64,
+    },
+
+    /// Submit the player's one ranked daily challenge attempt for `day`;
+    /// WPM and accuracy are derived from `stats`.
+    /// Accounts:
+    /// - [signer] Player account, or a session key scoped for submissions
+    /// - [writable] Daily challenge account for `day` (PDA)
+    /// - [writable] Player data account (PDA)
+    /// - [] Session key account (PDA, only when signing with a session key)
+    SubmitDailyResult {
+        day: i64,
+        stats: TypingStats,
+    },
+
+    /// Mint a one-off token for an unlocked achievement into the player's
+    /// associated token account. Each achievement can be claimed once.
+    /// Accounts:
+    /// - [signer, writable] Player account (pays for the mint and token account)
+    /// - [writable] Player data account (PDA)
+    /// - [writable] Achievement mint account (PDA, created here)
+    /// - [] Mint authority account (PDA)
+    /// - [writable] Player's associated token account for the mint (created if missing)
+    /// - [] System program
+    /// - [] SPL Token program
+    /// - [] Associated Token Account program
+    ClaimAchievement {
+        achievement: u8,
+    },
+
+    /// Create the fungible XP mint, with the program's mint authority PDA
+    /// as its only minter. Admin only.
+    /// Accounts:
+    /// - [signer, writable] Admin account
+    /// - [] Config account (PDA)
+    /// - [writable] XP mint account (PDA)
+    /// - [] Mint authority account (PDA)
+    /// - [] System program
+    /// - [] SPL Token program
+    InitializeXpMint,
+
+    /// Grow a player account created before `Player` carried a version to
+    /// the current layout, keeping its stats. Callable by anyone.
+    /// Accounts:
+    /// - [signer, writable] Fee payer (covers the extra rent)
+    /// - [writable] Player data account (PDA)
+    /// - [] System program
+    MigratePlayer,
+
+    /// Grow a contest account created before `Contest` carried a version to
+    /// the current layout, keeping its field and results. Callable by anyone.
+    /// Accounts:
+    /// - [signer, writable] Fee payer (covers the extra rent)
+    /// - [writable] Contest account
+    /// - [] System program
+    MigrateContest,
+
+    /// Draw the text of a contest that started without one, from the hash
+    /// of the slot it started in. Callable by anyone from the next slot on;
+    /// the race clock starts here.
+    /// Accounts:
+    /// - [writable] Contest data account
+    /// - [] Text registry account (PDA)
+    /// - [] SlotHashes sysvar
+    DrawContestText,
 }
 
 impl TypingInstruction {
@@ -1,18 +1,22 @@
 use solana_program::{
     account_info::AccountInfo,
-    entrypoint,
     entrypoint::ProgramResult,
     pubkey::Pubkey,
 };
 
+pub mod accounts;
+pub mod achievements;
 pub mod instruction;
 pub mod processor;
+pub mod rewards;
 pub mod state;
 pub mod error;
+pub mod events;
 
 use processor::Processor;
 
-entrypoint!(process_instruction);
+#[cfg(not(feature = "no-entrypoint"))]
+solana_program::entrypoint!(process_instruction);
 
 pub fn process_instruction(
     program_id: &Pubkey,
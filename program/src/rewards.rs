@@ -0,0 +1,21 @@
+/// Seed of the PDA that holds mint authority over every token the program
+/// issues: the XP mint and achievement mints.
+pub const MINT_AUTHORITY_SEED: &[u8] = b"mint_authority";
+/// Seed of the fungible XP mint, at `[b"xp_mint"]`.
+pub const XP_MINT_SEED: &[u8] = b"xp_mint";
+
+/// XP for typing `words` at `accuracy` percent: one per word, scaled down
+/// by accuracy.
+pub fn typing_xp(words: u64, accuracy: u32) -> u64 {
+    words.saturating_mul(accuracy as u64) / 100
+}
+
+/// Bonus XP for finishing a contest in `position` (1-based).
+pub fn placement_xp(position: u32) -> u64 {
+    match position {
+        1 => 100,
+        2 => 60,
+        3 => 30,
+        _ => 10,
+    }
+}
@@ -1,45 +1,1149 @@
 use borsh::{BorshDeserialize, BorshSerialize};
-use solana_program::pubkey::Pubkey;
+use core::cmp::Ordering;
+use crate::{
+    achievements::{Achievement, ACHIEVEMENTS},
+    error::TypingError,
+};
+use solana_program::{hash::hashv, pubkey::Pubkey};
+
+/// Characters per "word" in the standard WPM definition.
+pub const CHARS_PER_WORD: u64 = 5;
+
+/// Fastest WPM accepted at all; sustained human typing tops out well below.
+pub const MAX_PLAUSIBLE_WPM: u32 = 300;
+
+/// Minimum accuracy (percent) for a run to count towards a text record.
+pub const RECORD_MIN_ACCURACY: u32 = 95;
+
+pub const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// UTC day number of a unix timestamp.
+pub fn day_of(timestamp: i64) -> i64 {
+    timestamp.div_euclid(SECONDS_PER_DAY)
+}
+
+/// Reject results no human could have produced.
+pub fn verify_result(wpm: u32, accuracy: u32) -> Result<(), TypingError> {
+    if wpm > MAX_PLAUSIBLE_WPM || accuracy > 100 {
+        return Err(TypingError::ImplausibleResult);
+    }
+    Ok(())
+}
+
+/// Raw counts from one run, as reported by the client. WPM and accuracy
+/// are derived from these rather than taken on trust.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq)]
+pub struct TypingStats {
+    /// Characters left correct at the end of the run.
+    pub correct_chars: u32,
+    /// Characters left wrong at the end of the run.
+    pub incorrect_chars: u32,
+    /// Mistakes the player went back and fixed.
+    pub corrected_errors: u32,
+    /// Every key pressed, backspaces included.
+    pub total_keystrokes: u32,
+    pub elapsed_ms: u64,
+}
+
+impl TypingStats {
+    /// Check that the counts could have come from a real run: each
+    /// character takes a keystroke, and each corrected error a wrong key
+    /// plus a backspace on top.
+    pub fn validate(&self) -> Result<(), TypingError> {
+        let minimum_keystrokes = self.correct_chars as u64
+            + self.incorrect_chars as u64
+            + 2 * self.corrected_errors as u64;
+        if self.elapsed_ms == 0 || (self.total_keystrokes as u64) < minimum_keystrokes {
+            return Err(TypingError::ImplausibleResult);
+        }
+        verify_result(self.wpm()?, self.accuracy())
+    }
+    
+    /// Gross WPM: every character left on the page per minute, in words.
+    /// Scoring rules and contest modes apply any error penalty on top.
+    pub fn wpm(&self) -> Result<u32, TypingError> {
+        Self::per_minute(self.correct_chars as u64 + self.incorrect_chars as u64, self.elapsed_ms)
+    }
+    
+    /// Net WPM: correct characters per minute, in words.
+    pub fn net_wpm(&self) -> Result<u32, TypingError> {
+        Self::per_minute(self.correct_chars as u64, self.elapsed_ms)
+    }
+    
+    fn per_minute(chars: u64, elapsed_ms: u64) -> Result<u32, TypingError> {
+        let divisor = CHARS_PER_WORD
+            .checked_mul(elapsed_ms)
+            .filter(|&divisor| divisor > 0)
+            .ok_or(TypingError::ImplausibleResult)?;
+        u32::try_from(chars * 60_000 / divisor).map_err(|_| TypingError::ImplausibleResult)
+    }
+    
+    /// Percentage of characters typed right first time; corrected errors
+    /// count against it even though they no longer show.
+    pub fn accuracy(&self) -> u32 {
+        let attempted = self.correct_chars as u64 + self.incorrect_chars as u64 + self.corrected_errors as u64;
+        if attempted == 0 {
+            return 0;
+        }
+        (self.correct_chars as u64 * 100 / attempted) as u32
+    }
+    
+    /// Correct words typed, in the standard five-character words.
+    pub fn words(&self) -> u64 {
+        self.correct_chars as u64 / CHARS_PER_WORD
+    }
+    
+    /// Whole seconds elapsed, as contest results are timed.
+    pub fn seconds(&self) -> u64 {
+        self.elapsed_ms / 1000
+    }
+}
+
+/// A verified run of one text, as fed to the per-text trackers.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Attempt {
+    pub player: Pubkey,
+    pub text_id: u32,
+    pub wpm: u32,
+    pub accuracy: u32,
+    pub timestamp: i64,
+    /// `Ghost::trace_hash` of the run's replay, committed with the result
+    /// so that only that trace can later be published for it.
+    pub trace_hash: Option<[u8; 32]>,
+}
 
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
 pub struct Player {
+    /// Layout version; always `Player::VERSION` for an account of `SIZE`.
+    /// Older accounts are brought up to date with `MigratePlayer`.
+    pub version: u8,
     pub owner: Pubkey,
     pub total_tests: u32,
     pub best_wpm: u32,
     pub average_wpm: u32,
     pub best_accuracy: u32,
+    pub average_accuracy: u32,
     pub total_words_typed: u64,
+    pub chars_typed: u64,
+    // Running sums so averages are exact instead of drifting with each
+    // truncated incremental update.
+    pub wpm_sum: u64,
+    pub accuracy_sum: u64,
+    pub contest_record: ContestRecord,
+    /// Day number `practice_sessions_today` refers to.
+    pub practice_day: i64,
+    pub practice_sessions_today: u16,
+    /// When the last practice session was let through; 0 before the first.
+    /// The cooldown counts from this, so contests and daily runs don't
+    /// hold practice back.
+    pub last_practice_at: i64,
+    /// Consecutive days with a daily challenge attempt.
+    pub daily_streak: u32,
+    pub best_daily_streak: u32,
+    /// Day number of the last daily challenge attempt; 0 before the first.
+    pub last_daily_day: i64,
+    /// Consecutive UTC days with practice, possibly bridged by a freeze.
+    pub activity_streak: u32,
+    pub longest_activity_streak: u32,
+    /// Day number of the last practice session; 0 before the first.
+    pub last_active_day: i64,
+    /// Week number the last streak freeze was spent in.
+    pub last_freeze_week: i64,
+    /// Bit `id` is set for each unlocked achievement (see `ACHIEVEMENTS`).
+    pub achievements: u64,
+    /// Bit `id` is set once the achievement's token has been minted.
+    pub claimed_achievements: u64,
+    /// XP minted to this player today, against `xp_daily_player_cap`.
+    pub xp_tally: XpTally,
     pub created_at: i64,
     pub last_activity: i64,
 }
 
-impl Player {
+/// A player account as first deployed, before `Player` carried a version.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct LegacyPlayer {
+    pub owner: Pubkey,
+    pub total_tests: u32,
+    pub best_wpm: u32,
+    pub average_wpm: u32,
+    pub best_accuracy: u32,
+    pub total_words_typed: u64,
+    pub created_at: i64,
+    pub last_activity: i64,
+}
+
+impl LegacyPlayer {
     pub const SIZE: usize = 32 + 4 + 4 + 4 + 4 + 8 + 8 + 8;
+}
+
+impl Player {
+    pub const SEED: &'static [u8] = b"player";
+    pub const VERSION: u8 = 1;
+    pub const SIZE: usize =
+        1 + 32 + 4 + 4 + 4 + 4 + 4 + 8 + 8 + 8 + 8 + ContestRecord::SIZE + 8 + 2 + 8 + 4 + 4 + 8 + 4 + 4 + 8 + 8
+        + 8 + 8 + 8 + 8 + XpTally::SIZE;
+    /// Activity streak lengths that emit `StreakMilestone`.
+    pub const STREAK_MILESTONES: [u32; 3] = [7, 30, 100];
     
     pub fn new(owner: Pubkey, timestamp: i64) -> Self {
         Self {
+            version: Self::VERSION,
             owner,
             total_tests: 0,
             best_wpm: 0,
             average_wpm: 0,
             best_accuracy: 0,
+            average_accuracy: 0,
             total_words_typed: 0,
+            chars_typed: 0,
+            wpm_sum: 0,
+            accuracy_sum: 0,
+            contest_record: ContestRecord::default(),
+            practice_day: day_of(timestamp),
+            practice_sessions_today: 0,
+            last_practice_at: 0,
+            daily_streak: 0,
+            best_daily_streak: 0,
+            last_daily_day: 0,
+            activity_streak: 0,
+            longest_activity_streak: 0,
+            last_active_day: 0,
+            last_freeze_week: 0,
+            achievements: 0,
+            claimed_achievements: 0,
+            xp_tally: XpTally::default(),
             created_at: timestamp,
             last_activity: timestamp,
         }
     }
     
-    pub fn update_practice_stats(&mut self, wpm: u32, accuracy: u32, words_typed: u32, timestamp: i64) {
-        self.total_tests += 1;
+    /// Carry a pre-versioning account over to the current layout.
+    ///
+    /// The old layout kept no running sums or accuracy average, so the sums
+    /// are rebuilt from the averages it did keep, taking the best accuracy
+    /// as the average.
+    pub fn from_legacy(legacy: &LegacyPlayer) -> Self {
+        let tests = legacy.total_tests as u64;
+        Self {
+            total_tests: legacy.total_tests,
+            best_wpm: legacy.best_wpm,
+            average_wpm: legacy.average_wpm,
+            best_accuracy: legacy.best_accuracy,
+            average_accuracy: legacy.best_accuracy,
+            total_words_typed: legacy.total_words_typed,
+            chars_typed: legacy.total_words_typed.saturating_mul(CHARS_PER_WORD),
+            wpm_sum: legacy.average_wpm as u64 * tests,
+            accuracy_sum: legacy.best_accuracy as u64 * tests,
+            practice_day: day_of(legacy.last_activity),
+            last_activity: legacy.last_activity,
+            ..Self::new(legacy.owner, legacy.created_at)
+        }
+    }
+    
+    /// Fold in a practice run; `chars_typed` is its correct characters,
+    /// from which whole words are counted.
+    pub fn update_practice_stats(
+        &mut self,
+        wpm: u32,
+        accuracy: u32,
+        chars_typed: u64,
+        timestamp: i64,
+    ) -> Result<(), TypingError> {
+        let total_tests = self.total_tests.checked_add(1).ok_or(TypingError::ArithmeticOverflow)?;
+        let wpm_sum = self.wpm_sum.checked_add(wpm as u64).ok_or(TypingError::ArithmeticOverflow)?;
+        let accuracy_sum = self
+            .accuracy_sum
+            .checked_add(accuracy as u64)
+            .ok_or(TypingError::ArithmeticOverflow)?;
+        let total_words_typed = self
+            .total_words_typed
+            .checked_add(chars_typed / CHARS_PER_WORD)
+            .ok_or(TypingError::ArithmeticOverflow)?;
+        let chars_typed = self
+            .chars_typed
+            .checked_add(chars_typed)
+            .ok_or(TypingError::ArithmeticOverflow)?;
+        
+        // Both averages are bounded by the largest single u32 sample.
+        self.average_wpm = (wpm_sum / total_tests as u64) as u32;
+        self.average_accuracy = (accuracy_sum / total_tests as u64) as u32;
+        self.total_tests = total_tests;
+        self.wpm_sum = wpm_sum;
+        self.accuracy_sum = accuracy_sum;
+        self.total_words_typed = total_words_typed;
+        self.chars_typed = chars_typed;
         if wpm > self.best_wpm {
             self.best_wpm = wpm;
         }
         if accuracy > self.best_accuracy {
             self.best_accuracy = accuracy;
         }
-        self.total_words_typed += words_typed as u64;
-        self.average_wpm = (self.average_wpm * (self.total_tests - 1) + wpm) / self.total_tests;
+        self.record_active_day(day_of(timestamp))?;
         self.last_activity = timestamp;
+        Ok(())
+    }
+    
+    /// Extend the activity streak for practice on `day`.
+    ///
+    /// Missing a single day spends the week's streak freeze instead of
+    /// resetting the streak; at most one freeze is spent per week.
+    pub fn record_active_day(&mut self, day: i64) -> Result<(), TypingError> {
+        let week = day.div_euclid(7);
+        let streak = match day - self.last_active_day {
+            gap if gap <= 0 => return Ok(()),
+            1 => self.activity_streak.checked_add(1).ok_or(TypingError::ArithmeticOverflow)?,
+            2 if self.activity_streak > 0 && self.last_freeze_week != week => {
+                self.last_freeze_week = week;
+                self.activity_streak.checked_add(1).ok_or(TypingError::ArithmeticOverflow)?
+            }
+            _ => 1,
+        };
+        
+        self.activity_streak = streak;
+        self.longest_activity_streak = self.longest_activity_streak.max(streak);
+        self.last_active_day = day;
+        Ok(())
+    }
+    
+    /// Count a practice session against the configured cooldown and daily
+    /// cap, rejecting it if either is exceeded.
+    pub fn throttle_practice(&mut self, params: &ConfigParams, timestamp: i64) -> Result<(), TypingError> {
+        if self.last_practice_at != 0 && timestamp < self.last_practice_at.saturating_add(params.min_practice_interval) {
+            return Err(TypingError::PracticeRateLimited);
+        }
+        
+        let today = day_of(timestamp);
+        let sessions = if today == self.practice_day { self.practice_sessions_today } else { 0 };
+        if params.max_daily_practice_sessions != 0 && sessions >= params.max_daily_practice_sessions {
+            return Err(TypingError::PracticeRateLimited);
+        }
+        
+        self.practice_day = today;
+        self.practice_sessions_today = sessions.saturating_add(1);
+        self.last_practice_at = timestamp;
+        Ok(())
+    }
+    
+    /// Mark every achievement whose criteria are now met, returning the
+    /// newly unlocked ones.
+    pub fn unlock_achievements(&mut self) -> Vec<&'static Achievement> {
+        let unlocked: Vec<_> = ACHIEVEMENTS
+            .iter()
+            .filter(|achievement| !self.has_achievement(achievement.id) && (achievement.unlocked)(self))
+            .collect();
+        for achievement in &unlocked {
+            self.achievements |= 1 << achievement.id;
+        }
+        unlocked
+    }
+    
+    pub fn has_achievement(&self, id: u8) -> bool {
+        id < 64 && self.achievements & (1 << id) != 0
+    }
+    
+    /// Record the claim of an unlocked achievement's token.
+    pub fn claim_achievement(&mut self, id: u8) -> Result<(), TypingError> {
+        if !self.has_achievement(id) {
+            return Err(TypingError::AchievementLocked);
+        }
+        if self.claimed_achievements & (1 << id) != 0 {
+            return Err(TypingError::AchievementAlreadyClaimed);
+        }
+        self.claimed_achievements |= 1 << id;
+        Ok(())
+    }
+    
+    /// Use the player's one daily challenge attempt for `day`, extending
+    /// the streak if they also played the day before.
+    pub fn record_daily_attempt(&mut self, day: i64) -> Result<(), TypingError> {
+        if self.last_daily_day == day {
+            return Err(TypingError::DailyAttemptUsed);
+        }
+        let streak = if self.last_daily_day == day - 1 {
+            self.daily_streak.checked_add(1).ok_or(TypingError::ArithmeticOverflow)?
+        } else {
+            1
+        };
+        
+        self.daily_streak = streak;
+        self.best_daily_streak = self.best_daily_streak.max(streak);
+        self.last_daily_day = day;
+        Ok(())
+    }
+}
+
+/// A player's most recent results, at `[b"history", player]`.
+///
+/// The account holds this header followed by `CAPACITY` fixed-size
+/// `HistoryEntry` slots used as a ring buffer. Appends write one slot and
+/// the header in place rather than re-serializing the whole buffer.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq)]
+pub struct PlayerHistory {
+    pub player: Pubkey,
+    /// Slot the next entry goes into.
+    pub head: u16,
+    /// Filled slots, up to `CAPACITY`.
+    pub len: u16,
+}
+
+impl PlayerHistory {
+    pub const SEED: &'static [u8] = b"history";
+    pub const CAPACITY: usize = 50;
+    pub const HEADER_SIZE: usize = 32 + 2 + 2;
+    pub const SIZE: usize = Self::HEADER_SIZE + HistoryEntry::SIZE * Self::CAPACITY;
+    
+    pub fn new(player: Pubkey) -> Self {
+        Self { player, head: 0, len: 0 }
+    }
+    
+    /// Write `entry` over the oldest slot of the buffer in `data`.
+    pub fn append(data: &mut [u8], entry: &HistoryEntry) -> Result<(), TypingError> {
+        let (header_data, slots) = data.split_at_mut(Self::HEADER_SIZE);
+        let mut header = Self::try_from_slice(header_data).map_err(|_| TypingError::InvalidAccountData)?;
+        
+        let offset = header.head as usize * HistoryEntry::SIZE;
+        let slot = slots
+            .get_mut(offset..offset + HistoryEntry::SIZE)
+            .ok_or(TypingError::InvalidAccountData)?;
+        entry.serialize(&mut &mut slot[..]).map_err(|_| TypingError::InvalidAccountData)?;
+        
+        header.head = ((header.head as usize + 1) % Self::CAPACITY) as u16;
+        header.len = (header.len as usize + 1).min(Self::CAPACITY) as u16;
+        header.serialize(&mut &mut header_data[..]).map_err(|_| TypingError::InvalidAccountData)
+    }
+    
+    /// Stored entries, oldest first.
+    pub fn entries(data: &[u8]) -> Result<Vec<HistoryEntry>, TypingError> {
+        let header = Self::try_from_slice(&data[..Self::HEADER_SIZE]).map_err(|_| TypingError::InvalidAccountData)?;
+        let first = (header.head as usize + Self::CAPACITY - header.len as usize) % Self::CAPACITY;
+        (0..header.len as usize)
+            .map(|i| {
+                let offset = Self::HEADER_SIZE + (first + i) % Self::CAPACITY * HistoryEntry::SIZE;
+                HistoryEntry::try_from_slice(&data[offset..offset + HistoryEntry::SIZE])
+                    .map_err(|_| TypingError::InvalidAccountData)
+            })
+            .collect()
+    }
+}
+
+/// A player's per-key error counts and latency, at `[b"key_stats", player]`.
+///
+/// The account holds this header followed by one `KeyStat` slot per
+/// printable ASCII key, `FIRST_KEY` through `LAST_KEY`. Session deltas
+/// update their slots in place.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq)]
+pub struct KeyStats {
+    pub player: Pubkey,
+    /// Practice sessions folded in so far.
+    pub sessions: u32,
+}
+
+impl KeyStats {
+    pub const SEED: &'static [u8] = b"key_stats";
+    pub const FIRST_KEY: u8 = b' ';
+    pub const LAST_KEY: u8 = b'~';
+    pub const KEY_COUNT: usize = (Self::LAST_KEY - Self::FIRST_KEY) as usize + 1;
+    pub const HEADER_SIZE: usize = 32 + 4;
+    pub const SIZE: usize = Self::HEADER_SIZE + KeyStat::SIZE * Self::KEY_COUNT;
+    
+    pub fn new(player: Pubkey) -> Self {
+        Self { player, sessions: 0 }
+    }
+    
+    /// Check a session's deltas against the run they came with: one delta
+    /// per key at most, and no more presses, errors or time spent than the
+    /// run had.
+    pub fn validate_deltas(deltas: &[KeyDelta], stats: &TypingStats) -> Result<(), TypingError> {
+        let mut seen = [false; Self::KEY_COUNT];
+        let (mut presses, mut errors, mut latency_ms) = (0u64, 0u64, 0u64);
+        for delta in deltas {
+            let slot = Self::slot(delta.key)?;
+            if seen[slot] || delta.errors > delta.presses {
+                return Err(TypingError::InvalidKeyStats);
+            }
+            seen[slot] = true;
+            presses += delta.presses as u64;
+            errors += delta.errors as u64;
+            latency_ms += delta.latency_total_ms as u64;
+        }
+        if presses > stats.total_keystrokes as u64
+            || errors > stats.incorrect_chars as u64 + stats.corrected_errors as u64
+            || latency_ms > stats.elapsed_ms
+        {
+            return Err(TypingError::InvalidKeyStats);
+        }
+        Ok(())
+    }
+    
+    /// Add a session's deltas to the slots in `data`.
+    pub fn apply(data: &mut [u8], deltas: &[KeyDelta]) -> Result<(), TypingError> {
+        let (header_data, slots) = data.split_at_mut(Self::HEADER_SIZE);
+        let mut header = Self::try_from_slice(header_data).map_err(|_| TypingError::InvalidAccountData)?;
+        
+        for delta in deltas {
+            let offset = Self::slot(delta.key)? * KeyStat::SIZE;
+            let slot = slots
+                .get_mut(offset..offset + KeyStat::SIZE)
+                .ok_or(TypingError::InvalidAccountData)?;
+            let mut stat = KeyStat::try_from_slice(slot).map_err(|_| TypingError::InvalidAccountData)?;
+            stat.presses = stat
+                .presses
+                .checked_add(delta.presses as u32)
+                .ok_or(TypingError::ArithmeticOverflow)?;
+            stat.errors = stat
+                .errors
+                .checked_add(delta.errors as u32)
+                .ok_or(TypingError::ArithmeticOverflow)?;
+            stat.latency_total_ms = stat
+                .latency_total_ms
+                .checked_add(delta.latency_total_ms as u64)
+                .ok_or(TypingError::ArithmeticOverflow)?;
+            stat.serialize(&mut &mut slot[..]).map_err(|_| TypingError::InvalidAccountData)?;
+        }
+        
+        header.sessions = header.sessions.checked_add(1).ok_or(TypingError::ArithmeticOverflow)?;
+        header.serialize(&mut &mut header_data[..]).map_err(|_| TypingError::InvalidAccountData)
+    }
+    
+    /// Stats for `key`, read from the account in `data` without decoding
+    /// the other slots.
+    pub fn key(data: &[u8], key: u8) -> Result<KeyStat, TypingError> {
+        let offset = Self::HEADER_SIZE + Self::slot(key)? * KeyStat::SIZE;
+        data.get(offset..offset + KeyStat::SIZE)
+            .and_then(|slot| KeyStat::try_from_slice(slot).ok())
+            .ok_or(TypingError::InvalidAccountData)
+    }
+    
+    fn slot(key: u8) -> Result<usize, TypingError> {
+        if !(Self::FIRST_KEY..=Self::LAST_KEY).contains(&key) {
+            return Err(TypingError::InvalidKeyStats);
+        }
+        Ok((key - Self::FIRST_KEY) as usize)
+    }
+}
+
+/// Running totals for one key in a `KeyStats` account.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, Default, PartialEq)]
+pub struct KeyStat {
+    pub presses: u32,
+    pub errors: u32,
+    /// Sum of the time taken to reach this key, over every press.
+    pub latency_total_ms: u64,
+}
+
+impl KeyStat {
+    pub const SIZE: usize = 4 + 4 + 8;
+    
+    pub fn average_latency_ms(&self) -> u64 {
+        if self.presses == 0 {
+            return 0;
+        }
+        self.latency_total_ms / self.presses as u64
+    }
+}
+
+/// One key's counts from a single practice session.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq)]
+pub struct KeyDelta {
+    /// Printable ASCII code of the key.
+    pub key: u8,
+    pub presses: u16,
+    pub errors: u16,
+    pub latency_total_ms: u32,
+}
+
+/// One result in a `PlayerHistory`.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq)]
+pub struct HistoryEntry {
+    pub timestamp: i64,
+    pub wpm: u32,
+    pub accuracy: u32,
+    pub kind: ResultKind,
+}
+
+impl HistoryEntry {
+    pub const SIZE: usize = 8 + 4 + 4 + 1;
+}
+
+/// Where a result came from: practice, or a contest in a given mode.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq)]
+pub enum ResultKind {
+    Practice,
+    FixedText,
+    Timed,
+    SuddenDeath,
+    AccuracyGate,
+}
+
+impl From<ContestMode> for ResultKind {
+    fn from(mode: ContestMode) -> Self {
+        match mode {
+            ContestMode::FixedText => Self::FixedText,
+            ContestMode::Timed { .. } => Self::Timed,
+            ContestMode::SuddenDeath => Self::SuddenDeath,
+            ContestMode::AccuracyGate { .. } => Self::AccuracyGate,
+        }
+    }
+}
+
+/// Program-wide settings at `[b"config"]`, managed by `admin`.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct Config {
+    pub admin: Pubkey,
+    pub params: ConfigParams,
+    /// XP minted program-wide today, against `xp_daily_global_cap`. Only
+    /// instructions that mint XP write-lock the config to update it.
+    pub xp_tally: XpTally,
+}
+
+impl Config {
+    pub const SEED: &'static [u8] = b"config";
+    pub const SIZE: usize = 32 + ConfigParams::SIZE + XpTally::SIZE;
+    
+    pub fn new(admin: Pubkey, params: ConfigParams) -> Self {
+        Self {
+            admin,
+            params,
+            xp_tally: XpTally::default(),
+        }
+    }
+    
+    /// Grant up to `amount` XP on `day` within both the player's and the
+    /// program-wide daily caps, returning how much was granted.
+    pub fn award_xp(&mut self, player_tally: &mut XpTally, day: i64, amount: u64) -> u64 {
+        let granted = amount
+            .min(player_tally.remaining(day, self.params.xp_daily_player_cap))
+            .min(self.xp_tally.remaining(day, self.params.xp_daily_global_cap));
+        player_tally.record(day, granted);
+        self.xp_tally.record(day, granted);
+        granted
+    }
+}
+
+/// XP minted so far on one UTC day.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, Default, PartialEq)]
+pub struct XpTally {
+    pub day: i64,
+    pub minted: u64,
+}
+
+impl XpTally {
+    pub const SIZE: usize = 8 + 8;
+    
+    /// XP still available under `cap` on `day`.
+    pub fn remaining(&self, day: i64, cap: u64) -> u64 {
+        let minted = if self.day == day { self.minted } else { 0 };
+        cap.saturating_sub(minted)
+    }
+    
+    pub fn record(&mut self, day: i64, amount: u64) {
+        if self.day != day {
+            self.day = day;
+            self.minted = 0;
+        }
+        self.minted = self.minted.saturating_add(amount);
+    }
+}
+
+/// Texts available for automatic selection, at `[b"texts"]`, managed by the
+/// config admin.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Default, PartialEq)]
+pub struct TextRegistry {
+    pub texts: Vec<u32>,
+}
+
+impl TextRegistry {
+    pub const SEED: &'static [u8] = b"texts";
+    pub const MAX_TEXTS: usize = 256;
+    pub const SIZE: usize = 4 + 4 * Self::MAX_TEXTS;
+    
+    /// Add a text; registering one twice is a no-op.
+    pub fn register(&mut self, text_id: u32) -> Result<(), TypingError> {
+        if self.texts.contains(&text_id) {
+            return Ok(());
+        }
+        if self.texts.len() >= Self::MAX_TEXTS {
+            return Err(TypingError::TextRegistryFull);
+        }
+        self.texts.push(text_id);
+        Ok(())
+    }
+    
+    pub fn unregister(&mut self, text_id: u32) -> Result<(), TypingError> {
+        let index = self
+            .texts
+            .iter()
+            .position(|&t| t == text_id)
+            .ok_or(TypingError::TextNotRegistered)?;
+        // Keep the order so picks for a given seed stay reproducible.
+        self.texts.remove(index);
+        Ok(())
+    }
+    
+    /// Pick a text deterministically from `seed`.
+    pub fn pick(&self, seed: &[&[u8]]) -> Result<u32, TypingError> {
+        if self.texts.is_empty() {
+            return Err(TypingError::TextNotRegistered);
+        }
+        let hash = hashv(seed).to_bytes();
+        let mut prefix = [0u8; 8];
+        prefix.copy_from_slice(&hash[..8]);
+        Ok(self.texts[(u64::from_le_bytes(prefix) % self.texts.len() as u64) as usize])
+    }
+}
+
+/// The admin-tunable part of `Config`.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq)]
+pub struct ConfigParams {
+    /// Seconds a player must wait after a practice session before the next.
+    pub min_practice_interval: i64,
+    /// Practice sessions counted per player per UTC day; 0 disables the cap.
+    pub max_daily_practice_sessions: u16,
+    /// XP a single player can earn per UTC day; 0 stops XP rewards.
+    pub xp_daily_player_cap: u64,
+    /// XP minted across all players per UTC day.
+    pub xp_daily_global_cap: u64,
+}
+
+impl ConfigParams {
+    pub const SIZE: usize = 8 + 2 + 8 + 8;
+    
+    pub fn validate(&self) -> Result<(), TypingError> {
+        if self.min_practice_interval < 0 || self.xp_daily_player_cap > self.xp_daily_global_cap {
+            return Err(TypingError::InvalidConfig);
+        }
+        Ok(())
+    }
+}
+
+/// Ranked contest history, kept apart from the practice aggregates above.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Default, PartialEq)]
+pub struct ContestRecord {
+    /// Contests that ran, always `completed + dnfs`.
+    pub entered: u32,
+    pub completed: u32,
+    pub wins: u32,
+    pub podiums: u32,
+    pub dnfs: u32,
+    pub best_wpm: u32,
+    pub position_sum: u64,
+    /// Mean finishing position over completed contests, in hundredths.
+    pub average_position_x100: u32,
+}
+
+impl ContestRecord {
+    pub const SIZE: usize = 4 + 4 + 4 + 4 + 4 + 4 + 8 + 4;
+    
+    /// Fold a finalized contest outcome in; `None` means the player never
+    /// submitted. Disqualified runs count as DNFs.
+    ///
+    /// Only contests that ran get here, so a contest counts as entered now
+    /// rather than at join time, and a cancelled one never does.
+    pub fn record_outcome(&mut self, result: Option<&ContestResult>) -> Result<(), TypingError> {
+        self.entered = self.entered.checked_add(1).ok_or(TypingError::ArithmeticOverflow)?;
+        let Some(result) = result.filter(|r| !r.disqualified) else {
+            self.dnfs = self.dnfs.checked_add(1).ok_or(TypingError::ArithmeticOverflow)?;
+            return Ok(());
+        };
+        
+        let completed = self.completed.checked_add(1).ok_or(TypingError::ArithmeticOverflow)?;
+        let position_sum = self
+            .position_sum
+            .checked_add(result.position as u64)
+            .ok_or(TypingError::ArithmeticOverflow)?;
+        let average_position_x100 = position_sum
+            .checked_mul(100)
+            .map(|scaled| scaled / completed as u64)
+            .and_then(|avg| u32::try_from(avg).ok())
+            .ok_or(TypingError::ArithmeticOverflow)?;
+        
+        if result.position == 1 {
+            self.wins = self.wins.checked_add(1).ok_or(TypingError::ArithmeticOverflow)?;
+        }
+        if result.position <= 3 {
+            self.podiums = self.podiums.checked_add(1).ok_or(TypingError::ArithmeticOverflow)?;
+        }
+        if result.wpm > self.best_wpm {
+            self.best_wpm = result.wpm;
+        }
+        self.completed = completed;
+        self.position_sum = position_sum;
+        self.average_position_x100 = average_position_x100;
+        Ok(())
+    }
+}
+
+/// A player's best run on one text, at `[b"personal_best", player, text_id]`.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct PersonalBest {
+    pub player: Pubkey,
+    pub text_id: u32,
+    pub best_wpm: u32,
+    pub best_accuracy: u32,
+    pub attempts: u32,
+    pub best_at: i64,
+    pub last_attempt_at: i64,
+    /// `Ghost::trace_hash` committed with the best run; only meaningful
+    /// when `has_ghost` is set. Kept fixed-size so the layout is constant.
+    pub ghost_hash: [u8; 32],
+    /// Whether the best run committed a trace that can be published.
+    pub has_ghost: bool,
+}
+
+impl PersonalBest {
+    pub const SEED: &'static [u8] = b"personal_best";
+    pub const SIZE: usize = 32 + 4 + 4 + 4 + 4 + 8 + 8 + 32 + 1;
+    
+    pub fn new(player: Pubkey, text_id: u32) -> Self {
+        Self {
+            player,
+            text_id,
+            best_wpm: 0,
+            best_accuracy: 0,
+            attempts: 0,
+            best_at: 0,
+            last_attempt_at: 0,
+            ghost_hash: [0; 32],
+            has_ghost: false,
+        }
+    }
+    
+    /// Count an attempt, returning whether it set a new best WPM.
+    pub fn record_attempt(&mut self, attempt: &Attempt) -> Result<bool, TypingError> {
+        self.attempts = self.attempts.checked_add(1).ok_or(TypingError::ArithmeticOverflow)?;
+        self.last_attempt_at = attempt.timestamp;
+        if attempt.accuracy > self.best_accuracy {
+            self.best_accuracy = attempt.accuracy;
+        }
+        if attempt.wpm > self.best_wpm {
+            self.best_wpm = attempt.wpm;
+            self.best_at = attempt.timestamp;
+            // Only the new run's trace, if it committed one, may be published
+            self.ghost_hash = attempt.trace_hash.unwrap_or_default();
+            self.has_ghost = attempt.trace_hash.is_some();
+            return Ok(true);
+        }
+        Ok(false)
+    }
+}
+
+/// A replayable trace of a personal best, at `[b"ghost", player, text_id]`.
+///
+/// `samples[i]` is the number of characters completed after `i + 1`
+/// intervals of `interval_ms`.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct Ghost {
+    pub player: Pubkey,
+    pub text_id: u32,
+    /// Personal best WPM the trace was published for.
+    pub wpm: u32,
+    pub interval_ms: u16,
+    pub samples: Vec<u16>,
+    pub trace_hash: [u8; 32],
+    pub published_at: i64,
+}
+
+impl Ghost {
+    pub const SEED: &'static [u8] = b"ghost";
+    pub const MIN_INTERVAL_MS: u16 = 100;
+    pub const MAX_SAMPLES: usize = 600;
+    /// Allowed gap, in percent, between the trace's pace and the best WPM,
+    /// since the final interval is usually only partly used.
+    pub const WPM_TOLERANCE_PERCENT: u64 = 10;
+    pub const SIZE: usize = 32 + 4 + 4 + 2 + (4 + 2 * Self::MAX_SAMPLES) + 32 + 8;
+    
+    /// Check that a trace is well formed and replays at roughly `best_wpm`.
+    pub fn validate(interval_ms: u16, samples: &[u16], best_wpm: u32) -> Result<(), TypingError> {
+        if interval_ms < Self::MIN_INTERVAL_MS
+            || samples.is_empty()
+            || samples.len() > Self::MAX_SAMPLES
+            || samples.windows(2).any(|pair| pair[1] < pair[0])
+        {
+            return Err(TypingError::InvalidGhost);
+        }
+        
+        let chars = *samples.last().unwrap_or(&0) as u64;
+        let elapsed_ms = samples.len() as u64 * interval_ms as u64;
+        let trace_wpm = chars * 60_000 / (CHARS_PER_WORD * elapsed_ms);
+        let best_wpm = best_wpm as u64;
+        if best_wpm == 0
+            || trace_wpm * 100 < best_wpm * (100 - Self::WPM_TOLERANCE_PERCENT)
+            || trace_wpm * 100 > best_wpm * (100 + Self::WPM_TOLERANCE_PERCENT)
+        {
+            return Err(TypingError::InvalidGhost);
+        }
+        Ok(())
+    }
+    
+    pub fn trace_hash(interval_ms: u16, samples: &[u16]) -> [u8; 32] {
+        let samples: Vec<u8> = samples.iter().flat_map(|s| s.to_le_bytes()).collect();
+        hashv(&[&interval_ms.to_le_bytes(), &samples]).to_bytes()
+    }
+}
+
+/// A past holder of a text record.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct RecordEntry {
+    pub holder: Pubkey,
+    pub wpm: u32,
+    pub accuracy: u32,
+    pub set_at: i64,
+}
+
+impl RecordEntry {
+    pub const SIZE: usize = 32 + 4 + 4 + 8;
+}
+
+/// The fastest verified run of a text, at `[b"text_record", text_id]`.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct TextRecord {
+    pub text_id: u32,
+    pub current: Option<RecordEntry>,
+    /// Previous holders, oldest first, capped at `HISTORY_LEN`.
+    pub history: Vec<RecordEntry>,
+}
+
+impl TextRecord {
+    pub const SEED: &'static [u8] = b"text_record";
+    pub const HISTORY_LEN: usize = 10;
+    pub const SIZE: usize = 4 + (1 + RecordEntry::SIZE) + (4 + RecordEntry::SIZE * Self::HISTORY_LEN);
+    
+    pub fn new(text_id: u32) -> Self {
+        Self {
+            text_id,
+            current: None,
+            history: Vec::new(),
+        }
+    }
+    
+    /// Install `attempt` as the record if it beats the current one, returning
+    /// the dethroned entry (if there was one) on success.
+    pub fn try_break(&mut self, attempt: &Attempt) -> Option<Option<RecordEntry>> {
+        if attempt.accuracy < RECORD_MIN_ACCURACY {
+            return None;
+        }
+        if self.current.as_ref().is_some_and(|current| attempt.wpm <= current.wpm) {
+            return None;
+        }
+        
+        let entry = RecordEntry {
+            holder: attempt.player,
+            wpm: attempt.wpm,
+            accuracy: attempt.accuracy,
+            set_at: attempt.timestamp,
+        };
+        let previous = self.current.replace(entry);
+        if let Some(previous) = &previous {
+            if self.history.len() == Self::HISTORY_LEN {
+                self.history.remove(0);
+            }
+            self.history.push(previous.clone());
+        }
+        Some(previous)
+    }
+}
+
+/// Public profile for a player, at `[b"profile", owner]`.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct Profile {
+    pub owner: Pubkey,
+    pub display_name: String,
+    pub avatar_uri: String,
+    /// ISO 3166-1 alpha-2 code, or zeroes when unset.
+    pub country_code: [u8; 2],
+    pub updated_at: i64,
+}
+
+impl Profile {
+    pub const SEED: &'static [u8] = b"profile";
+    pub const MAX_AVATAR_URI_LEN: usize = 128;
+    pub const SIZE: usize = 32 + (4 + UsernameRecord::MAX_NAME_LEN) + (4 + Self::MAX_AVATAR_URI_LEN) + 2 + 8;
+    
+    pub fn validate(avatar_uri: &str, country_code: &[u8; 2]) -> Result<(), TypingError> {
+        if avatar_uri.len() > Self::MAX_AVATAR_URI_LEN {
+            return Err(TypingError::InvalidProfile);
+        }
+        if *country_code != [0, 0] && !country_code.iter().all(u8::is_ascii_uppercase) {
+            return Err(TypingError::InvalidProfile);
+        }
+        Ok(())
+    }
+}
+
+/// Claim on a normalized username, at `[b"username", normalized_name]`.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct UsernameRecord {
+    pub owner: Pubkey,
+    pub name: String,
+}
+
+impl UsernameRecord {
+    pub const SEED: &'static [u8] = b"username";
+    pub const MIN_NAME_LEN: usize = 3;
+    pub const MAX_NAME_LEN: usize = 20;
+    pub const SIZE: usize = 32 + 4 + Self::MAX_NAME_LEN;
+    
+    /// Registry key for a display name: ASCII letters, digits and `_`,
+    /// compared case-insensitively.
+    pub fn normalize(display_name: &str) -> Result<String, TypingError> {
+        let valid_len = (Self::MIN_NAME_LEN..=Self::MAX_NAME_LEN).contains(&display_name.len());
+        let valid_chars = display_name.bytes().all(|c| c.is_ascii_alphanumeric() || c == b'_');
+        if !valid_len || !valid_chars {
+            return Err(TypingError::InvalidUsername);
+        }
+        Ok(display_name.to_ascii_lowercase())
+    }
+}
+
+/// An ephemeral key allowed to act for a player in gameplay instructions,
+/// at `[b"session", owner, session_key]`.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct SessionKey {
+    pub owner: Pubkey,
+    pub session_key: Pubkey,
+    pub expires_at: i64,
+    /// Bitset of `SessionKey::SCOPE_*` flags.
+    pub scope: u8,
+    pub created_at: i64,
+}
+
+impl SessionKey {
+    pub const SEED: &'static [u8] = b"session";
+    pub const SIZE: usize = 32 + 32 + 8 + 1 + 8;
+    pub const MAX_DURATION: i64 = 7 * 24 * 60 * 60;
+    
+    pub const SCOPE_PRACTICE: u8 = 1 << 0;
+    pub const SCOPE_SUBMIT_RESULT: u8 = 1 << 1;
+    pub const SCOPE_REPORT_PROGRESS: u8 = 1 << 2;
+    /// Everything a session key may ever be granted. Instructions that move
+    /// funds or change ownership are deliberately absent.
+    pub const GAMEPLAY_SCOPES: u8 =
+        Self::SCOPE_PRACTICE | Self::SCOPE_SUBMIT_RESULT | Self::SCOPE_REPORT_PROGRESS;
+    
+    pub fn validate(scope: u8, expires_at: i64, timestamp: i64) -> Result<(), TypingError> {
+        if scope == 0 || scope & !Self::GAMEPLAY_SCOPES != 0 {
+            return Err(TypingError::InvalidSessionKey);
+        }
+        if expires_at <= timestamp || expires_at - timestamp > Self::MAX_DURATION {
+            return Err(TypingError::InvalidSessionKey);
+        }
+        Ok(())
+    }
+    
+    pub fn allows(&self, scope: u8, timestamp: i64) -> bool {
+        timestamp < self.expires_at && self.scope & scope == scope
+    }
+}
+
+/// Words typed over `time_taken` seconds at the given WPM.
+pub fn words_typed_at(wpm: u32, time_taken: u64) -> Result<u64, TypingError> {
+    (wpm as u64)
+        .checked_mul(time_taken)
+        .map(|total| total / 60)
+        .ok_or(TypingError::ArithmeticOverflow)
+}
+
+/// What a contest asks of its racers, and so how results are checked and
+/// ranked.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq)]
+pub enum ContestMode {
+    /// Type the whole text; fastest WPM wins.
+    FixedText,
+    /// Type for `seconds`; most correct words wins.
+    Timed { seconds: u32 },
+    /// The first error ends the run; longest clean run wins.
+    SuddenDeath,
+    /// As `FixedText`, but runs below `min` accuracy are disqualified.
+    AccuracyGate { min: u32 },
+}
+
+impl ContestMode {
+    pub const SIZE: usize = 1 + 4;
+    
+    pub fn validate(&self, duration: u64) -> Result<(), TypingError> {
+        match *self {
+            ContestMode::Timed { seconds } if seconds == 0 || seconds as u64 > duration => {
+                Err(TypingError::InvalidContestMode)
+            }
+            ContestMode::AccuracyGate { min } if min > 100 => Err(TypingError::InvalidContestMode),
+            _ => Ok(()),
+        }
+    }
+    
+    /// Reject results the mode makes impossible.
+    pub fn check_result(&self, accuracy: u32, time_taken: u64, duration: u64) -> Result<(), TypingError> {
+        let limit = match *self {
+            ContestMode::Timed { seconds } => seconds as u64,
+            _ => duration,
+        };
+        if time_taken == 0 || time_taken > limit {
+            return Err(TypingError::ImplausibleResult);
+        }
+        // A sudden-death run stops at its first error, so what was typed is clean.
+        if *self == ContestMode::SuddenDeath && accuracy != 100 {
+            return Err(TypingError::ImplausibleResult);
+        }
+        Ok(())
+    }
+    
+    pub fn disqualifies(&self, accuracy: u32) -> bool {
+        matches!(*self, ContestMode::AccuracyGate { min } if accuracy < min)
+    }
+    
+    /// Order two results best first.
+    pub fn rank(&self, a: &ContestResult, b: &ContestResult) -> Ordering {
+        // Characters typed, scaled by 60 * 100 / CHARS_PER_WORD, compared
+        // without dividing so no precision is lost.
+        let typed = |r: &ContestResult| r.wpm as u128 * r.time_taken as u128;
+        let correct = |r: &ContestResult| typed(r) * r.accuracy as u128;
+        let by_speed = || {
+            b.score
+                .cmp(&a.score)
+                .then(b.accuracy.cmp(&a.accuracy))
+                .then(b.wpm.cmp(&a.wpm))
+        };
+        
+        a.disqualified
+            .cmp(&b.disqualified)
+            .then_with(|| match self {
+                ContestMode::FixedText | ContestMode::AccuracyGate { .. } => by_speed(),
+                ContestMode::Timed { .. } => correct(b).cmp(&correct(a)).then(b.accuracy.cmp(&a.accuracy)),
+                ContestMode::SuddenDeath => typed(b).cmp(&typed(a)),
+            })
+            .then(a.time_taken.cmp(&b.time_taken))
+    }
+}
+
+/// How a contest combines WPM and accuracy into one score.
+///
+/// Scores are in hundredths of the rule's unit, so they stay exact integers.
+/// Modes ranked on pace (`FixedText`, `AccuracyGate`) order results by score;
+/// `Timed` and `SuddenDeath` rank on how much was typed and only record it.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq)]
+pub enum ScoringRule {
+    /// Gross WPM.
+    GrossWpm,
+    /// Gross WPM minus errors per minute.
+    NetWpm,
+    /// WPM × accuracy², accuracy taken as a fraction.
+    WpmAccuracySquared,
+    /// Accuracy percent alone.
+    Accuracy,
+}
+
+impl ScoringRule {
+    pub const SIZE: usize = 1;
+    
+    pub fn score(&self, wpm: u32, accuracy: u32) -> u64 {
+        let (wpm, accuracy) = (wpm as u64, accuracy.min(100) as u64);
+        match self {
+            ScoringRule::GrossWpm => wpm * 100,
+            // Errors per minute are the missed share of characters per minute:
+            // wpm * CHARS_PER_WORD * (100 - accuracy) / 100, here kept in hundredths.
+            ScoringRule::NetWpm => (wpm * 100).saturating_sub(wpm * CHARS_PER_WORD * (100 - accuracy)),
+            ScoringRule::WpmAccuracySquared => wpm * accuracy * accuracy / 100,
+            ScoringRule::Accuracy => accuracy * 100,
+        }
     }
 }
 
@@ -48,37 +1152,184 @@ pub enum ContestStatus {
     Waiting,
     Active,
     Ended,
+    /// A scheduled contest that reached its start with too few players.
+    Cancelled,
+}
+
+/// Announced timing for a contest that starts at a set time rather than as
+/// soon as a second player joins.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq)]
+pub struct ContestSchedule {
+    /// Last moment players may join.
+    pub join_deadline: i64,
+    /// When the contest may be cranked into `Active`.
+    pub scheduled_start: i64,
+}
+
+impl ContestSchedule {
+    pub const SIZE: usize = 8 + 8;
+    
+    pub fn validate(&self, timestamp: i64) -> Result<(), TypingError> {
+        if self.join_deadline <= timestamp || self.scheduled_start < self.join_deadline {
+            return Err(TypingError::InvalidSchedule);
+        }
+        Ok(())
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct Contest {
+    /// Layout version; always `Contest::VERSION` for an account of `SIZE`.
+    /// Older accounts are brought up to date with `MigrateContest`.
+    pub version: u8,
+    pub creator: Pubkey,
+    /// Fixed at creation, or `None` until drawn from the text registry after
+    /// the contest starts.
+    pub text_id: Option<u32>,
+    /// Slot whose hash draws the text, committed when a contest without a
+    /// text starts. Its hash is unknown until that slot has passed.
+    pub draw_slot: Option<u64>,
+    pub duration: u64,
+    pub mode: ContestMode,
+    pub scoring: ScoringRule,
+    pub schedule: Option<ContestSchedule>,
+    pub status: ContestStatus,
+    pub participants: Vec<Pubkey>,
+    pub results: Vec<ContestResult>,
+    pub created_at: i64,
+    pub started_at: Option<i64>,
+    pub ended_at: Option<i64>,
+    pub max_participants: u8,
+    /// Bit `i` is set once participant `i` has had this contest folded into
+    /// their `ContestRecord`.
+    pub outcomes_recorded: u16,
+    /// Live progress, parallel to `participants`.
+    pub progress: Vec<RaceProgress>,
 }
 
+/// A contest account as first deployed, before `Contest` carried a version.
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
-pub struct Contest {
+pub struct LegacyContest {
     pub creator: Pubkey,
     pub text_id: u32,
     pub duration: u64,
     pub status: ContestStatus,
     pub participants: Vec<Pubkey>,
-    pub results: Vec<ContestResult>,
+    pub results: Vec<LegacyContestResult>,
     pub created_at: i64,
     pub started_at: Option<i64>,
     pub ended_at: Option<i64>,
     pub max_participants: u8,
 }
 
+impl LegacyContest {
+    /// The size first-release contest accounts were allocated with.
+    pub const SIZE: usize = 32 + 4 + 8 + 1 + (32 * 10) + (64 * 10) + 8 + 9 + 9 + 1;
+}
+
+/// A `ContestResult` as first deployed.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct LegacyContestResult {
+    pub player: Pubkey,
+    pub wpm: u32,
+    pub accuracy: u32,
+    pub time_taken: u64,
+    pub position: u32,
+}
+
 impl Contest {
-    pub const SIZE: usize = 32 + 4 + 8 + 1 + (32 * 10) + (64 * 10) + 8 + 9 + 9 + 1; // Base size for 10 participants
+    pub const VERSION: u8 = 1;
+    /// Participants, and so results and progress entries, a contest holds.
+    pub const MAX_PARTICIPANTS: u8 = 10;
+    // Every Vec is sized for a full field behind its 4-byte length prefix.
+    pub const SIZE: usize = 1
+        + 32
+        + (1 + 4)
+        + (1 + 8)
+        + 8
+        + ContestMode::SIZE
+        + ScoringRule::SIZE
+        + (1 + ContestSchedule::SIZE)
+        + 1
+        + (4 + 32 * Self::MAX_PARTICIPANTS as usize)
+        + (4 + ContestResult::SIZE * Self::MAX_PARTICIPANTS as usize)
+        + 8
+        + (1 + 8)
+        + (1 + 8)
+        + 1
+        + 2
+        + (4 + RaceProgress::SIZE * Self::MAX_PARTICIPANTS as usize);
+    /// Players needed for a contest to start.
+    pub const MIN_PARTICIPANTS: usize = 2;
+    /// Progress reports each participant may post per race.
+    pub const MAX_PROGRESS_UPDATES: u8 = 30;
     
-    pub fn new(creator: Pubkey, text_id: u32, duration: u64, timestamp: i64) -> Self {
+    pub fn new(
+        creator: Pubkey,
+        text_id: Option<u32>,
+        duration: u64,
+        mode: ContestMode,
+        scoring: ScoringRule,
+        schedule: Option<ContestSchedule>,
+        timestamp: i64,
+    ) -> Self {
         Self {
+            version: Self::VERSION,
             creator,
             text_id,
+            draw_slot: None,
             duration,
+            mode,
+            scoring,
+            schedule,
             status: ContestStatus::Waiting,
             participants: Vec::new(),
             results: Vec::new(),
             created_at: timestamp,
             started_at: None,
             ended_at: None,
-            max_participants: 10,
+            max_participants: Self::MAX_PARTICIPANTS,
+            outcomes_recorded: 0,
+            progress: Vec::new(),
+        }
+    }
+    
+    /// Carry a first-release contest over to the current layout.
+    ///
+    /// The old layout only had fixed-text races ranked on gross WPM, so those
+    /// are the mode and scoring it gets, and each result is scored that way.
+    /// No participant had the contest folded into their record back then.
+    pub fn from_legacy(legacy: &LegacyContest) -> Self {
+        let scoring = ScoringRule::GrossWpm;
+        Self {
+            status: legacy.status.clone(),
+            participants: legacy.participants.clone(),
+            results: legacy
+                .results
+                .iter()
+                .map(|result| ContestResult {
+                    player: result.player,
+                    wpm: result.wpm,
+                    accuracy: result.accuracy,
+                    time_taken: result.time_taken,
+                    position: result.position,
+                    disqualified: false,
+                    score: scoring.score(result.wpm, result.accuracy),
+                })
+                .collect(),
+            started_at: legacy.started_at,
+            ended_at: legacy.ended_at,
+            max_participants: legacy.max_participants,
+            progress: vec![RaceProgress::default(); legacy.participants.len()],
+            ..Self::new(
+                legacy.creator,
+                Some(legacy.text_id),
+                legacy.duration,
+                ContestMode::FixedText,
+                scoring,
+                None,
+                legacy.created_at,
+            )
         }
     }
     
@@ -95,23 +1346,152 @@ impl Contest {
             return Err("Player already in contest");
         }
         self.participants.push(player);
+        self.progress.push(RaceProgress::default());
+        Ok(())
+    }
+    
+    pub fn join_deadline_passed(&self, timestamp: i64) -> bool {
+        self.schedule.is_some_and(|schedule| timestamp > schedule.join_deadline)
+    }
+    
+    /// Unscheduled contests start as soon as enough players have joined.
+    pub fn should_auto_start(&self) -> bool {
+        self.schedule.is_none() && self.participants.len() >= Self::MIN_PARTICIPANTS
+    }
+    
+    /// Move a scheduled contest out of `Waiting` once its start time has
+    /// come: `Active` with enough players, `Cancelled` otherwise.
+    pub fn crank_scheduled_start(&mut self, timestamp: i64) -> Result<(), TypingError> {
+        let schedule = self.schedule.ok_or(TypingError::InvalidSchedule)?;
+        if self.status != ContestStatus::Waiting {
+            return Err(TypingError::ContestAlreadyStarted);
+        }
+        if timestamp < schedule.scheduled_start {
+            return Err(TypingError::ScheduledStartNotReached);
+        }
+        
+        if self.participants.len() >= Self::MIN_PARTICIPANTS {
+            self.start_contest(timestamp);
+        } else {
+            self.status = ContestStatus::Cancelled;
+            self.ended_at = Some(timestamp);
+        }
         Ok(())
     }
     
+    /// Close the field. A contest without a text only starts its clock once
+    /// the text is drawn (see `reveal_text`).
     pub fn start_contest(&mut self, timestamp: i64) {
         if self.status == ContestStatus::Waiting {
             self.status = ContestStatus::Active;
-            self.started_at = Some(timestamp);
+            if self.text_id.is_some() {
+                self.started_at = Some(timestamp);
+            }
+        }
+    }
+    
+    /// Commit a contest that has just started without a text to drawing it
+    /// from the hash of `slot`, the slot it started in.
+    pub fn commit_draw(&mut self, slot: u64) {
+        if self.status == ContestStatus::Active && self.text_id.is_none() {
+            self.draw_slot = Some(slot);
+        }
+    }
+    
+    /// The committed draw slot of an active contest still waiting on its text.
+    pub fn pending_draw(&self) -> Result<u64, TypingError> {
+        if self.status != ContestStatus::Active {
+            return Err(TypingError::ContestNotActive);
+        }
+        if self.text_id.is_some() {
+            return Err(TypingError::ContestAlreadyStarted);
         }
+        self.draw_slot.ok_or(TypingError::ContestNotActive)
+    }
+    
+    /// Set the drawn text and start the race clock.
+    pub fn reveal_text(&mut self, text_id: u32, timestamp: i64) {
+        self.text_id = Some(text_id);
+        self.started_at = Some(timestamp);
     }
     
     pub fn end_contest(&mut self, timestamp: i64) {
         if self.status == ContestStatus::Active {
             self.status = ContestStatus::Ended;
             self.ended_at = Some(timestamp);
+            self.finalize_positions();
         }
     }
     
+    /// Whether the race window is over, so stragglers can be marked DNF.
+    pub fn has_expired(&self, timestamp: i64) -> bool {
+        match self.started_at {
+            Some(started_at) => timestamp >= started_at.saturating_add(i64::try_from(self.duration).unwrap_or(i64::MAX)),
+            None => false,
+        }
+    }
+    
+    /// Rank submitted results by the contest mode. Remaining ties keep
+    /// submission order; disqualified results get position 0.
+    pub fn finalize_positions(&mut self) {
+        let mut order: Vec<usize> = (0..self.results.len()).collect();
+        order.sort_by(|&a, &b| self.mode.rank(&self.results[a], &self.results[b]));
+        for (rank, index) in order.into_iter().enumerate() {
+            let result = &mut self.results[index];
+            result.position = if result.disqualified { 0 } else { rank as u32 + 1 };
+        }
+    }
+    
+    /// Mark participant `player`'s outcome as recorded, returning their result
+    /// (`None` for a DNF).
+    pub fn take_outcome(&mut self, player: &Pubkey) -> Result<Option<&ContestResult>, TypingError> {
+        if self.status != ContestStatus::Ended {
+            return Err(TypingError::ContestNotEnded);
+        }
+        let index = self
+            .participants
+            .iter()
+            .position(|p| p == player)
+            .ok_or(TypingError::PlayerNotInContest)?;
+        let bit = 1u16 << index;
+        if self.outcomes_recorded & bit != 0 {
+            return Err(TypingError::OutcomeAlreadyRecorded);
+        }
+        self.outcomes_recorded |= bit;
+        Ok(self.results.iter().find(|r| r.player == *player))
+    }
+    
+    /// Record a live checkpoint for `player`. Progress never moves backwards,
+    /// and each participant gets `MAX_PROGRESS_UPDATES` reports per race.
+    pub fn report_progress(
+        &mut self,
+        player: &Pubkey,
+        chars_completed: u16,
+        wpm: u16,
+        timestamp: i64,
+    ) -> Result<(), TypingError> {
+        if self.status != ContestStatus::Active || self.started_at.is_none() || self.has_expired(timestamp) {
+            return Err(TypingError::ContestNotActive);
+        }
+        let index = self
+            .participants
+            .iter()
+            .position(|p| p == player)
+            .ok_or(TypingError::PlayerNotInContest)?;
+        let progress = &mut self.progress[index];
+        if progress.updates >= Self::MAX_PROGRESS_UPDATES {
+            return Err(TypingError::ProgressLimitReached);
+        }
+        if u32::from(wpm) > MAX_PLAUSIBLE_WPM || chars_completed < progress.chars_completed {
+            return Err(TypingError::ImplausibleResult);
+        }
+        
+        progress.chars_completed = chars_completed;
+        progress.wpm = wpm;
+        progress.updates += 1;
+        Ok(())
+    }
+    
     pub fn submit_result(&mut self, player: Pubkey, wpm: u32, accuracy: u32, time_taken: u64) -> Result<(), &'static str> {
         if self.status != ContestStatus::Active {
             return Err("Contest not active");
@@ -131,12 +1511,26 @@ impl Contest {
             accuracy,
             time_taken,
             position: 0, // Will be calculated after all results are in
+            disqualified: self.mode.disqualifies(accuracy),
+            score: self.scoring.score(wpm, accuracy),
         });
         
         Ok(())
     }
 }
 
+/// A participant's latest live checkpoint in a race.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, Default, PartialEq)]
+pub struct RaceProgress {
+    pub chars_completed: u16,
+    pub wpm: u16,
+    pub updates: u8,
+}
+
+impl RaceProgress {
+    pub const SIZE: usize = 2 + 2 + 1;
+}
+
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
 pub struct ContestResult {
     pub player: Pubkey,
@@ -144,4 +1538,980 @@ pub struct ContestResult {
     pub accuracy: u32,
     pub time_taken: u64,
     pub position: u32,
-}
\ No newline at end of file
+    pub disqualified: bool,
+    /// `Contest::scoring` applied to this run.
+    pub score: u64,
+}
+
+impl ContestResult {
+    pub const SIZE: usize = 32 + 4 + 4 + 8 + 4 + 1 + 8;
+}
+
+/// The shared contest for one UTC day, at `[b"daily", day]`.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct DailyChallenge {
+    pub day: i64,
+    pub text_id: u32,
+    pub attempts: u32,
+    /// Best runs of the day, best first, capped at `LEADERBOARD_LEN`.
+    pub leaderboard: Vec<DailyEntry>,
+}
+
+impl DailyChallenge {
+    pub const SEED: &'static [u8] = b"daily";
+    pub const LEADERBOARD_LEN: usize = 10;
+    pub const SIZE: usize = 8 + 4 + 4 + 4 + DailyEntry::SIZE * Self::LEADERBOARD_LEN;
+    
+    pub fn new(day: i64, text_id: u32) -> Self {
+        Self {
+            day,
+            text_id,
+            attempts: 0,
+            leaderboard: Vec::new(),
+        }
+    }
+    
+    /// Count an attempt, returning its 1-based leaderboard rank if it made
+    /// the board. Ties go to whoever submitted first.
+    pub fn submit(&mut self, entry: DailyEntry) -> Result<Option<u8>, TypingError> {
+        self.attempts = self.attempts.checked_add(1).ok_or(TypingError::ArithmeticOverflow)?;
+        let index = self
+            .leaderboard
+            .iter()
+            .position(|e| (entry.wpm, entry.accuracy) > (e.wpm, e.accuracy))
+            .unwrap_or(self.leaderboard.len());
+        if index >= Self::LEADERBOARD_LEN {
+            return Ok(None);
+        }
+        self.leaderboard.insert(index, entry);
+        self.leaderboard.truncate(Self::LEADERBOARD_LEN);
+        Ok(Some(index as u8 + 1))
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq)]
+pub struct DailyEntry {
+    pub player: Pubkey,
+    pub wpm: u32,
+    pub accuracy: u32,
+    pub submitted_at: i64,
+}
+
+impl DailyEntry {
+    pub const SIZE: usize = 32 + 4 + 4 + 8;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn test_params() -> ConfigParams {
+        ConfigParams {
+            min_practice_interval: 10,
+            max_daily_practice_sessions: 3,
+            xp_daily_player_cap: 500,
+            xp_daily_global_cap: 100_000,
+        }
+    }
+    
+    #[test]
+    fn test_full_contest_fits_its_account() {
+        let mut contest = Contest::new(
+            Pubkey::new_unique(),
+            Some(u32::MAX),
+            u64::MAX,
+            ContestMode::Timed { seconds: u32::MAX },
+            ScoringRule::NetWpm,
+            Some(ContestSchedule { join_deadline: 1, scheduled_start: 2 }),
+            0,
+        );
+        for _ in 0..Contest::MAX_PARTICIPANTS {
+            let player = Pubkey::new_unique();
+            contest.add_participant(player).unwrap();
+            contest.results.push(ContestResult {
+                player,
+                wpm: u32::MAX,
+                accuracy: 100,
+                time_taken: u64::MAX,
+                position: 1,
+                disqualified: false,
+                score: u64::MAX,
+            });
+        }
+        contest.start_contest(1);
+        contest.draw_slot = Some(u64::MAX);
+        contest.end_contest(2);
+        
+        assert_eq!(contest.participants.len(), Contest::MAX_PARTICIPANTS as usize);
+        assert_eq!(contest.progress.len(), Contest::MAX_PARTICIPANTS as usize);
+        assert_eq!(contest.try_to_vec().unwrap().len(), Contest::SIZE);
+    }
+
+    #[test]
+    fn test_player_data_serialization() {
+        let payer = Pubkey::new_unique();
+        let timestamp = 1234567890;
+        
+        let player = Player::new(payer, timestamp);
+        
+        // Test serialization
+        let mut data = vec![0; Player::SIZE];
+        player.serialize(&mut data.as_mut_slice()).unwrap();
+        
+        // Test deserialization
+        let deserialized_player = Player::try_from_slice(&data).unwrap();
+        
+        assert_eq!(player.owner, deserialized_player.owner);
+        assert_eq!(player.total_tests, deserialized_player.total_tests);
+        assert_eq!(player.best_wpm, deserialized_player.best_wpm);
+        assert_eq!(player.average_wpm, deserialized_player.average_wpm);
+        assert_eq!(player.best_accuracy, deserialized_player.best_accuracy);
+        assert_eq!(player.total_words_typed, deserialized_player.total_words_typed);
+        assert_eq!(player.wpm_sum, deserialized_player.wpm_sum);
+        assert_eq!(player.accuracy_sum, deserialized_player.accuracy_sum);
+        assert_eq!(player.chars_typed, deserialized_player.chars_typed);
+        assert_eq!(player.created_at, deserialized_player.created_at);
+        assert_eq!(player.last_activity, deserialized_player.last_activity);
+    }
+
+    #[test]
+    fn test_player_creation() {
+        let owner = Pubkey::new_unique();
+        let timestamp = 1640995200; // Jan 1, 2022
+        
+        let player = Player::new(owner, timestamp);
+        
+        assert_eq!(player.owner, owner);
+        assert_eq!(player.total_tests, 0);
+        assert_eq!(player.best_wpm, 0);
+        assert_eq!(player.average_wpm, 0);
+        assert_eq!(player.best_accuracy, 0);
+        assert_eq!(player.average_accuracy, 0);
+        assert_eq!(player.total_words_typed, 0);
+        assert_eq!(player.created_at, timestamp);
+        assert_eq!(player.last_activity, timestamp);
+    }
+
+    #[test]
+    fn test_practice_stats_exact_averages() {
+        let mut player = Player::new(Pubkey::new_unique(), 0);
+        
+        player.update_practice_stats(61, 90, 150, 1).unwrap();
+        player.update_practice_stats(60, 95, 152, 2).unwrap();
+        player.update_practice_stats(62, 99, 150, 3).unwrap();
+        
+        // Truncating the running average after every test would give 60 here
+        assert_eq!(player.wpm_sum, 183);
+        assert_eq!(player.average_wpm, 61);
+        assert_eq!(player.accuracy_sum, 284);
+        assert_eq!(player.average_accuracy, 94);
+        assert_eq!(player.total_words_typed, 90);
+        // Characters are kept exactly; words only count whole ones
+        assert_eq!(player.chars_typed, 452);
+        assert_eq!(player.best_wpm, 62);
+        assert_eq!(player.best_accuracy, 99);
+        assert_eq!(player.last_activity, 3);
+    }
+
+    #[test]
+    fn test_practice_stats_overflow_is_rejected() {
+        let mut player = Player::new(Pubkey::new_unique(), 0);
+        player.wpm_sum = u64::MAX - 10;
+        player.total_tests = 5;
+        
+        let result = player.update_practice_stats(u32::MAX, 100, 50, 1);
+        
+        assert!(matches!(result, Err(TypingError::ArithmeticOverflow)));
+        // Nothing is partially applied on failure
+        assert_eq!(player.total_tests, 5);
+        assert_eq!(player.wpm_sum, u64::MAX - 10);
+    }
+
+    #[test]
+    fn test_words_typed_at_does_not_overflow_u32() {
+        // The old `wpm * time_taken as u32` wrapped for values like these
+        assert_eq!(words_typed_at(200, 30_000_000).unwrap(), 100_000_000);
+        assert!(matches!(
+            words_typed_at(u32::MAX, u64::MAX),
+            Err(TypingError::ArithmeticOverflow)
+        ));
+    }
+
+    #[test]
+    fn test_contest_positions_and_outcomes() {
+        let creator = Pubkey::new_unique();
+        let (fast, accurate, slow, absent) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let mut contest = Contest::new(creator, Some(1), 60, ContestMode::FixedText, ScoringRule::GrossWpm, None, 0);
+        for player in [fast, accurate, slow, absent] {
+            contest.add_participant(player).unwrap();
+        }
+        contest.start_contest(10);
+        contest.submit_result(slow, 50, 99, 60).unwrap();
+        contest.submit_result(accurate, 90, 99, 60).unwrap();
+        contest.submit_result(fast, 90, 95, 60).unwrap();
+        
+        // Outcomes can't be taken before the contest is over
+        assert!(matches!(contest.take_outcome(&fast), Err(TypingError::ContestNotEnded)));
+        assert!(!contest.has_expired(69));
+        assert!(contest.has_expired(70));
+        contest.end_contest(70);
+        
+        let position = |p: Pubkey| contest.results.iter().find(|r| r.player == p).unwrap().position;
+        assert_eq!(position(accurate), 1);
+        assert_eq!(position(fast), 2);
+        assert_eq!(position(slow), 3);
+        
+        let mut record = ContestRecord::default();
+        record.record_outcome(contest.take_outcome(&accurate).unwrap()).unwrap();
+        record.record_outcome(contest.take_outcome(&absent).unwrap()).unwrap();
+        assert_eq!(record.entered, 2);
+        assert_eq!(record.completed, 1);
+        assert_eq!(record.wins, 1);
+        assert_eq!(record.podiums, 1);
+        assert_eq!(record.dnfs, 1);
+        assert_eq!(record.best_wpm, 90);
+        assert_eq!(record.average_position_x100, 100);
+        
+        // Each participant's outcome is only counted once
+        assert!(matches!(
+            contest.take_outcome(&accurate),
+            Err(TypingError::OutcomeAlreadyRecorded)
+        ));
+        assert!(matches!(
+            contest.take_outcome(&creator),
+            Err(TypingError::PlayerNotInContest)
+        ));
+    }
+
+    #[test]
+    fn test_contest_record_average_position() {
+        let mut record = ContestRecord::default();
+        let result = |position| ContestResult {
+            player: Pubkey::new_unique(),
+            wpm: 70,
+            accuracy: 95,
+            time_taken: 60,
+            position,
+            disqualified: false,
+            score: 7000,
+        };
+        
+        record.record_outcome(Some(&result(1))).unwrap();
+        record.record_outcome(Some(&result(4))).unwrap();
+        
+        assert_eq!(record.completed, 2);
+        assert_eq!(record.wins, 1);
+        assert_eq!(record.podiums, 1);
+        assert_eq!(record.average_position_x100, 250);
+    }
+
+    #[test]
+    fn test_personal_best_tracks_attempts() {
+        let player = Pubkey::new_unique();
+        let attempt = |wpm, accuracy, timestamp| Attempt {
+            player,
+            text_id: 3,
+            wpm,
+            accuracy,
+            timestamp,
+            trace_hash: None,
+        };
+        let mut personal_best = PersonalBest::new(player, 3);
+        
+        assert!(personal_best.record_attempt(&attempt(70, 92, 100)).unwrap());
+        assert!(!personal_best.record_attempt(&attempt(65, 98, 200)).unwrap());
+        assert!(personal_best.record_attempt(&attempt(75, 90, 300)).unwrap());
+        
+        assert_eq!(personal_best.attempts, 3);
+        assert_eq!(personal_best.best_wpm, 75);
+        assert_eq!(personal_best.best_accuracy, 98);
+        assert_eq!(personal_best.best_at, 300);
+        assert_eq!(personal_best.last_attempt_at, 300);
+    }
+
+    #[test]
+    fn test_text_record_history() {
+        let attempt = |player, wpm, accuracy, timestamp| Attempt {
+            player,
+            text_id: 9,
+            wpm,
+            accuracy,
+            timestamp,
+            trace_hash: None,
+        };
+        let first = Pubkey::new_unique();
+        let mut record = TextRecord::new(9);
+        
+        assert_eq!(record.try_break(&attempt(first, 100, 96, 1)), Some(None));
+        // Too sloppy to count, and not faster either
+        assert_eq!(record.try_break(&attempt(Pubkey::new_unique(), 150, 80, 2)), None);
+        assert_eq!(record.try_break(&attempt(Pubkey::new_unique(), 100, 99, 3)), None);
+        
+        for i in 0..TextRecord::HISTORY_LEN as u32 + 2 {
+            let previous = record.try_break(&attempt(Pubkey::new_unique(), 101 + i, 97, 10 + i as i64));
+            assert_eq!(previous.unwrap().unwrap().wpm, 100 + i);
+        }
+        
+        let current = record.current.as_ref().unwrap();
+        assert_eq!(current.wpm, 112);
+        assert_eq!(record.history.len(), TextRecord::HISTORY_LEN);
+        // The oldest holders fell off the end
+        assert_eq!(record.history[0].wpm, 102);
+        assert!(record.history.iter().all(|entry| entry.holder != first));
+        
+        let mut data = vec![0; TextRecord::SIZE];
+        record.serialize(&mut data.as_mut_slice()).unwrap();
+        assert_eq!(TextRecord::deserialize(&mut &data[..]).unwrap(), record);
+    }
+
+    #[test]
+    fn test_verify_result_rejects_impossible_runs() {
+        assert!(verify_result(120, 98).is_ok());
+        assert!(matches!(verify_result(MAX_PLAUSIBLE_WPM + 1, 98), Err(TypingError::ImplausibleResult)));
+        assert!(matches!(verify_result(80, 101), Err(TypingError::ImplausibleResult)));
+    }
+
+    #[test]
+    fn test_typing_stats_derive_metrics() {
+        let stats = TypingStats {
+            correct_chars: 270,
+            incorrect_chars: 20,
+            corrected_errors: 10,
+            total_keystrokes: 320,
+            elapsed_ms: 40_000,
+        };
+        stats.validate().unwrap();
+        // Gross counts the 20 wrong characters too; net leaves them out
+        assert_eq!(stats.wpm().unwrap(), 87);
+        assert_eq!(stats.net_wpm().unwrap(), 81);
+        assert_eq!(stats.accuracy(), 90);
+        assert_eq!(stats.words(), 54);
+        assert_eq!(stats.seconds(), 40);
+        
+        // Each corrected error needs a wrong key and a backspace
+        let too_few_keystrokes = TypingStats { total_keystrokes: 309, ..stats };
+        assert!(matches!(too_few_keystrokes.validate(), Err(TypingError::ImplausibleResult)));
+        let no_time = TypingStats { elapsed_ms: 0, ..stats };
+        assert!(matches!(no_time.validate(), Err(TypingError::ImplausibleResult)));
+        let too_fast = TypingStats { elapsed_ms: 1_000, ..stats };
+        assert!(matches!(too_fast.validate(), Err(TypingError::ImplausibleResult)));
+    }
+
+    #[test]
+    fn test_username_normalization() {
+        assert_eq!(UsernameRecord::normalize("Speedy_Fingers").unwrap(), "speedy_fingers");
+        assert!(matches!(UsernameRecord::normalize("ab"), Err(TypingError::InvalidUsername)));
+        assert!(matches!(UsernameRecord::normalize("has space"), Err(TypingError::InvalidUsername)));
+        assert!(matches!(UsernameRecord::normalize("ünïcode"), Err(TypingError::InvalidUsername)));
+        assert!(matches!(
+            UsernameRecord::normalize(&"x".repeat(UsernameRecord::MAX_NAME_LEN + 1)),
+            Err(TypingError::InvalidUsername)
+        ));
+        
+        assert!(Profile::validate("https://example.com/a.png", b"FR").is_ok());
+        assert!(Profile::validate("", &[0, 0]).is_ok());
+        assert!(matches!(Profile::validate("", b"fr"), Err(TypingError::InvalidProfile)));
+        assert!(matches!(
+            Profile::validate(&"a".repeat(Profile::MAX_AVATAR_URI_LEN + 1), &[0, 0]),
+            Err(TypingError::InvalidProfile)
+        ));
+    }
+
+    #[test]
+    fn test_session_key_scope_and_expiry() {
+        let now = 1_000;
+        assert!(SessionKey::validate(SessionKey::SCOPE_PRACTICE, now + 3600, now).is_ok());
+        assert!(matches!(
+            SessionKey::validate(0, now + 3600, now),
+            Err(TypingError::InvalidSessionKey)
+        ));
+        assert!(matches!(
+            SessionKey::validate(0b1000_0000, now + 3600, now),
+            Err(TypingError::InvalidSessionKey)
+        ));
+        assert!(matches!(
+            SessionKey::validate(SessionKey::SCOPE_PRACTICE, now, now),
+            Err(TypingError::InvalidSessionKey)
+        ));
+        assert!(matches!(
+            SessionKey::validate(SessionKey::SCOPE_PRACTICE, now + SessionKey::MAX_DURATION + 1, now),
+            Err(TypingError::InvalidSessionKey)
+        ));
+        
+        let session = SessionKey {
+            owner: Pubkey::new_unique(),
+            session_key: Pubkey::new_unique(),
+            expires_at: now + 60,
+            scope: SessionKey::SCOPE_PRACTICE,
+            created_at: now,
+        };
+        assert!(session.allows(SessionKey::SCOPE_PRACTICE, now));
+        assert!(!session.allows(SessionKey::SCOPE_SUBMIT_RESULT, now));
+        assert!(!session.allows(SessionKey::SCOPE_PRACTICE, now + 60));
+    }
+
+    #[test]
+    fn test_practice_throttle_cooldown_and_daily_cap() {
+        let params = test_params();
+        let day_start = 100 * 86_400;
+        let mut player = Player::new(Pubkey::new_unique(), day_start);
+        
+        // A new player's first session isn't held back by account creation
+        let mut now = day_start + 5;
+        player.throttle_practice(&params, now).unwrap();
+        player.update_practice_stats(80, 95, 200, now).unwrap();
+        
+        // The cooldown counts from that session
+        assert!(matches!(
+            player.throttle_practice(&params, now + 5),
+            Err(TypingError::PracticeRateLimited)
+        ));
+        
+        for _ in 1..params.max_daily_practice_sessions {
+            now += 60;
+            player.throttle_practice(&params, now).unwrap();
+            player.update_practice_stats(80, 95, 200, now).unwrap();
+        }
+        assert_eq!(player.practice_sessions_today, 3);
+        assert!(matches!(
+            player.throttle_practice(&params, now + 60),
+            Err(TypingError::PracticeRateLimited)
+        ));
+        
+        // The cap resets at the next UTC day
+        player.throttle_practice(&params, day_start + 86_400).unwrap();
+        assert_eq!(player.practice_sessions_today, 1);
+        assert_eq!(player.total_tests, 3);
+        
+        let negative_interval = ConfigParams { min_practice_interval: -1, ..params };
+        assert!(matches!(negative_interval.validate(), Err(TypingError::InvalidConfig)));
+    }
+
+    #[test]
+    fn test_practice_cooldown_ignores_contest_and_daily_play() {
+        let params = test_params();
+        let day = 100;
+        let start = day * 86_400;
+        let mut player = Player::new(Pubkey::new_unique(), start);
+        player.throttle_practice(&params, start).unwrap();
+        
+        // A contest submission and a daily run, as their handlers record them
+        player.last_activity = start + 8;
+        player.record_daily_attempt(day).unwrap();
+        player.last_activity = start + 9;
+        
+        player.throttle_practice(&params, start + params.min_practice_interval).unwrap();
+        assert_eq!(player.practice_sessions_today, 2);
+    }
+
+    #[test]
+    fn test_report_progress_is_monotonic_and_throttled() {
+        let (alice, bob, outsider) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let mut contest = Contest::new(Pubkey::new_unique(), Some(1), 60, ContestMode::FixedText, ScoringRule::GrossWpm, None, 0);
+        contest.add_participant(alice).unwrap();
+        contest.add_participant(bob).unwrap();
+        
+        assert!(matches!(
+            contest.report_progress(&alice, 10, 50, 5),
+            Err(TypingError::ContestNotActive)
+        ));
+        contest.start_contest(10);
+        
+        contest.report_progress(&bob, 40, 80, 15).unwrap();
+        assert_eq!(contest.progress[1], RaceProgress { chars_completed: 40, wpm: 80, updates: 1 });
+        assert!(matches!(
+            contest.report_progress(&bob, 30, 80, 16),
+            Err(TypingError::ImplausibleResult)
+        ));
+        assert!(matches!(
+            contest.report_progress(&outsider, 10, 50, 16),
+            Err(TypingError::PlayerNotInContest)
+        ));
+        
+        for chars in 0..Contest::MAX_PROGRESS_UPDATES as u16 {
+            contest.report_progress(&alice, chars, 60, 20).unwrap();
+        }
+        assert!(matches!(
+            contest.report_progress(&alice, 100, 60, 21),
+            Err(TypingError::ProgressLimitReached)
+        ));
+        assert!(matches!(
+            contest.report_progress(&bob, 50, 80, 70),
+            Err(TypingError::ContestNotActive)
+        ));
+        
+        // Still fits the fixed-size account with a full field
+        for _ in 2..contest.max_participants {
+            contest.participants.push(Pubkey::new_unique());
+            contest.progress.push(RaceProgress::default());
+        }
+        assert!(contest.try_to_vec().unwrap().len() <= Contest::SIZE);
+    }
+
+    #[test]
+    fn test_ghost_validation_and_hash_commitment() {
+        // 60 WPM is 5 chars per second: one sample per second for 10 seconds
+        let samples: Vec<u16> = (1..=10).map(|second| second * 5).collect();
+        assert!(Ghost::validate(1000, &samples, 60).is_ok());
+        assert!(Ghost::validate(1000, &samples, 64).is_ok());
+        
+        assert!(matches!(Ghost::validate(1000, &samples, 80), Err(TypingError::InvalidGhost)));
+        assert!(matches!(Ghost::validate(1000, &samples, 0), Err(TypingError::InvalidGhost)));
+        assert!(matches!(Ghost::validate(50, &samples, 60), Err(TypingError::InvalidGhost)));
+        assert!(matches!(Ghost::validate(1000, &[], 60), Err(TypingError::InvalidGhost)));
+        assert!(matches!(Ghost::validate(1000, &[10, 5], 60), Err(TypingError::InvalidGhost)));
+        
+        let hash = Ghost::trace_hash(1000, &samples);
+        assert_ne!(hash, Ghost::trace_hash(500, &samples));
+        
+        let ghost = Ghost {
+            player: Pubkey::new_unique(),
+            text_id: 3,
+            wpm: 60,
+            interval_ms: Ghost::MIN_INTERVAL_MS,
+            samples: vec![u16::MAX; Ghost::MAX_SAMPLES],
+            trace_hash: hash,
+            published_at: 0,
+        };
+        assert_eq!(ghost.try_to_vec().unwrap().len(), Ghost::SIZE);
+        
+        // Only the trace committed with the current best stays publishable
+        let player = Pubkey::new_unique();
+        let mut pb = PersonalBest::new(player, 3);
+        let attempt = |wpm, trace_hash| Attempt { player, text_id: 3, wpm, accuracy: 97, timestamp: 1, trace_hash };
+        pb.record_attempt(&attempt(60, Some(hash))).unwrap();
+        assert_eq!((pb.has_ghost, pb.ghost_hash), (true, hash));
+        pb.record_attempt(&attempt(55, Some([7; 32]))).unwrap();
+        assert_eq!((pb.has_ghost, pb.ghost_hash), (true, hash));
+        pb.record_attempt(&attempt(70, None)).unwrap();
+        assert!(!pb.has_ghost);
+        assert_eq!(pb.try_to_vec().unwrap().len(), PersonalBest::SIZE);
+    }
+
+    #[test]
+    fn test_contest_modes_validate_and_rank() {
+        assert!(ContestMode::Timed { seconds: 30 }.validate(60).is_ok());
+        assert!(matches!(ContestMode::Timed { seconds: 0 }.validate(60), Err(TypingError::InvalidContestMode)));
+        assert!(matches!(ContestMode::Timed { seconds: 90 }.validate(60), Err(TypingError::InvalidContestMode)));
+        assert!(matches!(ContestMode::AccuracyGate { min: 101 }.validate(60), Err(TypingError::InvalidContestMode)));
+        
+        assert!(ContestMode::FixedText.check_result(90, 60, 60).is_ok());
+        assert!(matches!(
+            ContestMode::Timed { seconds: 30 }.check_result(90, 45, 60),
+            Err(TypingError::ImplausibleResult)
+        ));
+        assert!(matches!(
+            ContestMode::SuddenDeath.check_result(99, 20, 60),
+            Err(TypingError::ImplausibleResult)
+        ));
+        
+        let race = |mode| {
+            let players: Vec<Pubkey> = (0..3).map(|_| Pubkey::new_unique()).collect();
+            let mut contest = Contest::new(Pubkey::new_unique(), Some(1), 60, mode, ScoringRule::GrossWpm, None, 0);
+            for player in &players {
+                contest.add_participant(*player).unwrap();
+            }
+            contest.start_contest(0);
+            // Fast but sloppy, steady and accurate, slow and short
+            contest.submit_result(players[0], 100, 80, 30).unwrap();
+            contest.submit_result(players[1], 90, 100, 30).unwrap();
+            contest.submit_result(players[2], 60, 100, 20).unwrap();
+            contest.end_contest(60);
+            contest.results.iter().map(|r| r.position).collect::<Vec<_>>()
+        };
+        
+        assert_eq!(race(ContestMode::FixedText), vec![1, 2, 3]);
+        // 100 * 80% < 90 * 100% correct words in the same window
+        assert_eq!(race(ContestMode::Timed { seconds: 30 }), vec![2, 1, 3]);
+        // Longest run wins, whatever its pace
+        assert_eq!(race(ContestMode::SuddenDeath), vec![1, 2, 3]);
+        assert_eq!(race(ContestMode::AccuracyGate { min: 90 }), vec![0, 1, 2]);
+        
+        let mut record = ContestRecord::default();
+        let disqualified = ContestResult {
+            player: Pubkey::new_unique(),
+            wpm: 100,
+            accuracy: 80,
+            time_taken: 30,
+            position: 0,
+            disqualified: true,
+            score: 0,
+        };
+        record.record_outcome(Some(&disqualified)).unwrap();
+        assert_eq!((record.completed, record.dnfs), (0, 1));
+    }
+
+    #[test]
+    fn test_scoring_rules() {
+        assert_eq!(ScoringRule::GrossWpm.score(80, 95), 8000);
+        // 80 WPM at 95% misses 20 characters a minute
+        assert_eq!(ScoringRule::NetWpm.score(80, 95), 6000);
+        assert_eq!(ScoringRule::NetWpm.score(40, 70), 0);
+        assert_eq!(ScoringRule::WpmAccuracySquared.score(80, 95), 7220);
+        assert_eq!(ScoringRule::Accuracy.score(80, 95), 9500);
+        assert_eq!(ScoringRule::WpmAccuracySquared.score(MAX_PLAUSIBLE_WPM, 100), 30000);
+        
+        // Under net WPM the accurate typist beats the faster, sloppier one
+        let (fast, accurate) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut contest = Contest::new(Pubkey::new_unique(), Some(1), 60, ContestMode::FixedText, ScoringRule::NetWpm, None, 0);
+        contest.add_participant(fast).unwrap();
+        contest.add_participant(accurate).unwrap();
+        contest.start_contest(0);
+        contest.submit_result(fast, 100, 90, 40).unwrap();
+        contest.submit_result(accurate, 80, 99, 50).unwrap();
+        contest.end_contest(60);
+        
+        assert_eq!(contest.results[0].score, 5000);
+        assert_eq!(contest.results[1].score, 7600);
+        assert_eq!(contest.results[0].position, 2);
+        assert_eq!(contest.results[1].position, 1);
+    }
+
+    #[test]
+    fn test_contest_ranks_results_derived_from_stats() {
+        // A minute each: 300 characters with 50 left wrong, against 270 clean
+        let sloppy = TypingStats {
+            correct_chars: 250,
+            incorrect_chars: 50,
+            corrected_errors: 0,
+            total_keystrokes: 300,
+            elapsed_ms: 60_000,
+        };
+        let clean = TypingStats {
+            correct_chars: 270,
+            incorrect_chars: 0,
+            corrected_errors: 0,
+            total_keystrokes: 270,
+            elapsed_ms: 60_000,
+        };
+        let race = |mode, scoring| {
+            let players = [Pubkey::new_unique(), Pubkey::new_unique()];
+            let mut contest = Contest::new(Pubkey::new_unique(), Some(1), 60, mode, scoring, None, 0);
+            for player in &players {
+                contest.add_participant(*player).unwrap();
+            }
+            contest.start_contest(0);
+            for (player, stats) in players.iter().zip([sloppy, clean]) {
+                stats.validate().unwrap();
+                contest.submit_result(*player, stats.wpm().unwrap(), stats.accuracy(), stats.seconds()).unwrap();
+            }
+            contest.end_contest(60);
+            contest.results.iter().map(|r| (r.score, r.position)).collect::<Vec<_>>()
+        };
+        
+        // Gross WPM rewards the extra characters, errors and all
+        assert_eq!(race(ContestMode::FixedText, ScoringRule::GrossWpm), vec![(6000, 1), (5400, 2)]);
+        // 60 gross WPM less 50 errors a minute, penalised once
+        assert_eq!(race(ContestMode::FixedText, ScoringRule::NetWpm), vec![(900, 2), (5400, 1)]);
+        // 250 correct characters lose to 270 in the same window
+        let timed = race(ContestMode::Timed { seconds: 60 }, ScoringRule::GrossWpm);
+        assert_eq!((timed[0].1, timed[1].1), (2, 1));
+    }
+
+    #[test]
+    fn test_scheduled_contest_start_and_cancel() {
+        let schedule = ContestSchedule { join_deadline: 100, scheduled_start: 120 };
+        assert!(schedule.validate(50).is_ok());
+        assert!(matches!(schedule.validate(100), Err(TypingError::InvalidSchedule)));
+        assert!(matches!(
+            ContestSchedule { join_deadline: 100, scheduled_start: 90 }.validate(50),
+            Err(TypingError::InvalidSchedule)
+        ));
+        
+        let scheduled = || {
+            Contest::new(Pubkey::new_unique(), Some(1), 60, ContestMode::FixedText, ScoringRule::GrossWpm, Some(schedule), 50)
+        };
+        
+        let mut contest = scheduled();
+        contest.add_participant(Pubkey::new_unique()).unwrap();
+        contest.add_participant(Pubkey::new_unique()).unwrap();
+        // Waits for the announced time instead of starting at two players
+        assert!(!contest.should_auto_start());
+        assert!(!contest.join_deadline_passed(100));
+        assert!(contest.join_deadline_passed(101));
+        assert!(matches!(
+            contest.crank_scheduled_start(119),
+            Err(TypingError::ScheduledStartNotReached)
+        ));
+        contest.crank_scheduled_start(125).unwrap();
+        assert_eq!(contest.status, ContestStatus::Active);
+        assert_eq!(contest.started_at, Some(125));
+        assert!(matches!(
+            contest.crank_scheduled_start(126),
+            Err(TypingError::ContestAlreadyStarted)
+        ));
+        
+        let mut lonely = scheduled();
+        lonely.add_participant(Pubkey::new_unique()).unwrap();
+        lonely.crank_scheduled_start(120).unwrap();
+        assert_eq!(lonely.status, ContestStatus::Cancelled);
+        assert!(!lonely.can_join());
+        
+        let mut unscheduled = Contest::new(Pubkey::new_unique(), Some(1), 60, ContestMode::FixedText, ScoringRule::GrossWpm, None, 0);
+        assert!(matches!(unscheduled.crank_scheduled_start(500), Err(TypingError::InvalidSchedule)));
+    }
+
+    #[test]
+    fn test_text_registry_pick_is_deterministic() {
+        let mut registry = TextRegistry::default();
+        assert!(matches!(registry.pick(&[b"seed"]), Err(TypingError::TextNotRegistered)));
+        
+        registry.register(7).unwrap();
+        registry.register(7).unwrap();
+        registry.register(9).unwrap();
+        registry.register(11).unwrap();
+        assert_eq!(registry.texts, vec![7, 9, 11]);
+        
+        let day = 20_000i64.to_le_bytes();
+        let picked = registry.pick(&[DailyChallenge::SEED, &day]).unwrap();
+        assert!(registry.texts.contains(&picked));
+        assert_eq!(registry.pick(&[DailyChallenge::SEED, &day]).unwrap(), picked);
+        
+        registry.unregister(9).unwrap();
+        assert_eq!(registry.texts, vec![7, 11]);
+        assert!(matches!(registry.unregister(9), Err(TypingError::TextNotRegistered)));
+        
+        let mut full = TextRegistry {
+            texts: (0..TextRegistry::MAX_TEXTS as u32).collect(),
+        };
+        assert!(matches!(full.register(u32::MAX), Err(TypingError::TextRegistryFull)));
+        assert!(full.try_to_vec().unwrap().len() <= TextRegistry::SIZE);
+    }
+
+    #[test]
+    fn test_daily_challenge_leaderboard_and_streaks() {
+        let mut daily = DailyChallenge::new(100, 7);
+        let entry = |wpm, accuracy, submitted_at| DailyEntry {
+            player: Pubkey::new_unique(),
+            wpm,
+            accuracy,
+            submitted_at,
+        };
+        
+        assert_eq!(daily.submit(entry(80, 95, 1)).unwrap(), Some(1));
+        assert_eq!(daily.submit(entry(90, 90, 2)).unwrap(), Some(1));
+        // Ties go to the earlier submission
+        assert_eq!(daily.submit(entry(80, 95, 3)).unwrap(), Some(3));
+        for i in 0..7 {
+            daily.submit(entry(50, 90, 10 + i)).unwrap();
+        }
+        assert_eq!(daily.leaderboard.len(), DailyChallenge::LEADERBOARD_LEN);
+        assert_eq!(daily.submit(entry(10, 90, 20)).unwrap(), None);
+        assert_eq!(daily.attempts, 11);
+        assert!(daily.try_to_vec().unwrap().len() <= DailyChallenge::SIZE);
+        
+        let mut player = Player::new(Pubkey::new_unique(), 0);
+        player.record_daily_attempt(100).unwrap();
+        assert!(matches!(player.record_daily_attempt(100), Err(TypingError::DailyAttemptUsed)));
+        player.record_daily_attempt(101).unwrap();
+        player.record_daily_attempt(102).unwrap();
+        assert_eq!(player.daily_streak, 3);
+        // A missed day resets the streak but keeps the best
+        player.record_daily_attempt(104).unwrap();
+        assert_eq!(player.daily_streak, 1);
+        assert_eq!(player.best_daily_streak, 3);
+    }
+
+    #[test]
+    fn test_contest_text_draw_is_committed_at_start() {
+        let alice = Pubkey::new_unique();
+        let mut contest = Contest::new(Pubkey::new_unique(), None, 60, ContestMode::FixedText, ScoringRule::GrossWpm, None, 0);
+        contest.add_participant(alice).unwrap();
+        contest.add_participant(Pubkey::new_unique()).unwrap();
+        assert!(matches!(contest.pending_draw(), Err(TypingError::ContestNotActive)));
+        
+        // The field closes, but the clock waits for the text
+        contest.start_contest(10);
+        contest.commit_draw(42);
+        assert_eq!((contest.started_at, contest.pending_draw().unwrap()), (None, 42));
+        assert!(!contest.has_expired(1_000));
+        assert!(matches!(
+            contest.report_progress(&alice, 10, 50, 11),
+            Err(TypingError::ContestNotActive)
+        ));
+        contest.reveal_text(5, 20);
+        assert_eq!((contest.text_id, contest.started_at), (Some(5), Some(20)));
+        assert!(matches!(contest.pending_draw(), Err(TypingError::ContestAlreadyStarted)));
+    }
+
+    #[test]
+    fn test_activity_streak_with_weekly_freeze() {
+        // Day 20000 is the second day of its week
+        let day = 20_000i64;
+        let mut player = Player::new(Pubkey::new_unique(), 0);
+        player.update_practice_stats(60, 95, 250, day * SECONDS_PER_DAY).unwrap();
+        player.update_practice_stats(60, 95, 250, day * SECONDS_PER_DAY + 3600).unwrap();
+        assert_eq!(player.activity_streak, 1);
+        assert_eq!(player.last_active_day, day);
+        
+        player.record_active_day(day + 1).unwrap();
+        // Missing one day spends the week's freeze
+        player.record_active_day(day + 3).unwrap();
+        assert_eq!(player.activity_streak, 3);
+        assert_eq!(player.last_freeze_week, (day + 3).div_euclid(7));
+        
+        // A second miss in the same week breaks the streak
+        let mut same_week = player.clone();
+        same_week.record_active_day(day + 5).unwrap();
+        assert_eq!(same_week.activity_streak, 1);
+        
+        // The freeze is available again next week
+        let next_week = (day + 3).div_euclid(7) * 7 + 7;
+        
+        for d in day + 4..next_week {
+            player.record_active_day(d).unwrap();
+        }
+        let streak = player.activity_streak;
+        player.record_active_day(next_week + 1).unwrap();
+        assert_eq!(player.activity_streak, streak + 1);
+        
+        player.record_active_day(next_week + 5).unwrap();
+        assert_eq!(player.activity_streak, 1);
+        assert_eq!(player.longest_activity_streak, streak + 1);
+        assert!(Player::STREAK_MILESTONES.contains(&7));
+        assert_eq!(player.try_to_vec().unwrap().len(), Player::SIZE);
+    }
+
+    #[test]
+    fn test_achievements_unlock_once() {
+        use crate::achievements::{self, ACHIEVEMENTS};
+        
+        for (index, achievement) in ACHIEVEMENTS.iter().enumerate() {
+            assert!(achievement.id < 64);
+            assert!(ACHIEVEMENTS[..index].iter().all(|other| other.id != achievement.id));
+        }
+        
+        let mut player = Player::new(Pubkey::new_unique(), 0);
+        assert!(player.unlock_achievements().is_empty());
+        
+        player.update_practice_stats(105, 100, 250, 0).unwrap();
+        let names: Vec<_> = player.unlock_achievements().iter().map(|a| a.name).collect();
+        assert_eq!(names, vec!["Century", "Flawless"]);
+        assert!(player.has_achievement(achievements::find(0).unwrap().id));
+        // Already unlocked achievements do not fire again
+        assert!(player.unlock_achievements().is_empty());
+        
+        player.contest_record.wins = 1;
+        player.longest_activity_streak = 30;
+        let ids: Vec<_> = player.unlock_achievements().iter().map(|a| a.id).collect();
+        assert_eq!(ids, vec![3, 4]);
+        assert_eq!(player.achievements, 0b11101);
+    }
+
+    #[test]
+    fn test_config_award_xp_respects_daily_caps() {
+        let mut config = Config::new(Pubkey::new_unique(), test_params());
+        config.params.xp_daily_global_cap = 700;
+        let mut alice = Player::new(Pubkey::new_unique(), 0);
+        let mut bob = Player::new(Pubkey::new_unique(), 0);
+        
+        assert_eq!(config.award_xp(&mut alice.xp_tally, 1, 300), 300);
+        // Alice hits her own cap of 500
+        assert_eq!(config.award_xp(&mut alice.xp_tally, 1, 300), 200);
+        assert_eq!(config.award_xp(&mut alice.xp_tally, 1, 300), 0);
+        // Bob is limited by what is left of the global cap
+        assert_eq!(config.award_xp(&mut bob.xp_tally, 1, 400), 200);
+        assert_eq!(config.xp_tally, XpTally { day: 1, minted: 700 });
+        
+        // Both caps reset the next day
+        assert_eq!(config.award_xp(&mut alice.xp_tally, 2, 300), 300);
+        assert_eq!(alice.xp_tally, XpTally { day: 2, minted: 300 });
+        
+        let mut params = config.params;
+        params.xp_daily_player_cap = params.xp_daily_global_cap + 1;
+        assert!(matches!(params.validate(), Err(TypingError::InvalidConfig)));
+    }
+
+    #[test]
+    fn test_player_history_ring_buffer_wraps() {
+        let player = Pubkey::new_unique();
+        let mut data = vec![0; PlayerHistory::SIZE];
+        PlayerHistory::new(player).serialize(&mut &mut data[..]).unwrap();
+        assert!(PlayerHistory::entries(&data).unwrap().is_empty());
+        
+        let entry = |i: usize| HistoryEntry {
+            timestamp: i as i64,
+            wpm: 40 + i as u32,
+            accuracy: 90,
+            kind: [ResultKind::Practice, ContestMode::Timed { seconds: 30 }.into()][i % 2],
+        };
+        for i in 0..3 {
+            PlayerHistory::append(&mut data, &entry(i)).unwrap();
+        }
+        assert_eq!(PlayerHistory::entries(&data).unwrap(), vec![entry(0), entry(1), entry(2)]);
+        
+        // Past capacity the oldest entries are overwritten in place
+        for i in 3..PlayerHistory::CAPACITY + 5 {
+            PlayerHistory::append(&mut data, &entry(i)).unwrap();
+        }
+        let entries = PlayerHistory::entries(&data).unwrap();
+        assert_eq!(entries.len(), PlayerHistory::CAPACITY);
+        assert_eq!(entries[0], entry(5));
+        assert_eq!(entries[PlayerHistory::CAPACITY - 1], entry(PlayerHistory::CAPACITY + 4));
+        assert_eq!(entries[0].kind, ResultKind::Timed);
+        
+        let header = PlayerHistory::try_from_slice(&data[..PlayerHistory::HEADER_SIZE]).unwrap();
+        assert_eq!(header, PlayerHistory { player, head: 5, len: PlayerHistory::CAPACITY as u16 });
+        assert_eq!(data.len(), PlayerHistory::SIZE);
+    }
+
+    #[test]
+    fn test_key_stats_accumulate_deltas() {
+        let player = Pubkey::new_unique();
+        let mut data = vec![0; KeyStats::SIZE];
+        KeyStats::new(player).serialize(&mut &mut data[..]).unwrap();
+        let stats = TypingStats {
+            correct_chars: 270,
+            incorrect_chars: 20,
+            corrected_errors: 10,
+            total_keystrokes: 320,
+            elapsed_ms: 40_000,
+        };
+        let delta = |key: u8, presses: u16, errors: u16| KeyDelta {
+            key,
+            presses,
+            errors,
+            latency_total_ms: presses as u32 * 150,
+        };
+        
+        let deltas = [delta(b'e', 40, 6), delta(b'~', 2, 1)];
+        KeyStats::validate_deltas(&deltas, &stats).unwrap();
+        KeyStats::apply(&mut data, &deltas).unwrap();
+        KeyStats::apply(&mut data, &[delta(b'e', 10, 0)]).unwrap();
+        
+        let e = KeyStats::key(&data, b'e').unwrap();
+        assert_eq!(e, KeyStat { presses: 50, errors: 6, latency_total_ms: 7_500 });
+        assert_eq!(e.average_latency_ms(), 150);
+        assert_eq!(KeyStats::key(&data, b'~').unwrap().errors, 1);
+        assert_eq!(KeyStats::key(&data, b' ').unwrap(), KeyStat::default());
+        let header = KeyStats::try_from_slice(&data[..KeyStats::HEADER_SIZE]).unwrap();
+        assert_eq!(header, KeyStats { player, sessions: 2 });
+        
+        // Outside printable ASCII, repeated keys, more errors or presses than
+        // the run had, and more time spent on keys than the run took
+        for bad in [
+            vec![delta(b'\n', 1, 0)],
+            vec![delta(b'a', 1, 0), delta(b'a', 1, 0)],
+            vec![delta(b'a', 1, 2)],
+            vec![delta(b'a', 40, 31)],
+            vec![delta(b'a', 321, 0)],
+            vec![KeyDelta { latency_total_ms: 40_001, ..delta(b'a', 1, 0) }],
+        ] {
+            assert!(matches!(KeyStats::validate_deltas(&bad, &stats), Err(TypingError::InvalidKeyStats)));
+        }
+        
+        // Totals that would wrap fail instead of sticking at the maximum
+        let offset = KeyStats::HEADER_SIZE + (b'e' - KeyStats::FIRST_KEY) as usize * KeyStat::SIZE;
+        let full = KeyStat { presses: u32::MAX, ..e };
+        full.serialize(&mut &mut data[offset..offset + KeyStat::SIZE]).unwrap();
+        assert!(matches!(
+            KeyStats::apply(&mut data, &[delta(b'e', 1, 0)]),
+            Err(TypingError::ArithmeticOverflow)
+        ));
+    }
+}
@@ -0,0 +1,109 @@
+//! Runs against `solana-program-test`, which installs process-wide syscall
+//! stubs, so it lives apart from the unit tests in `processor.rs`.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    instruction::{AccountMeta, Instruction, InstructionError},
+    program_option::COption,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    system_program,
+};
+use solana_program_test::{processor, tokio, ProgramTest, ProgramTestBanksClientExt};
+use solana_sdk::{
+    account::Account,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use spl_token::state::{Account as TokenAccount, Mint};
+use typing_master_program::{
+    achievements, error::TypingError, instruction::TypingInstruction, process_instruction, rewards, state::Player,
+};
+
+fn claim_instruction(program_id: &Pubkey, player: &Pubkey, achievement: u8) -> (Pubkey, Pubkey, Instruction) {
+    let (player_pda, _) = Pubkey::find_program_address(&[Player::SEED, player.as_ref()], program_id);
+    let (mint, _) = Pubkey::find_program_address(&[achievements::MINT_SEED, player.as_ref(), &[achievement]], program_id);
+    let (mint_authority, _) = Pubkey::find_program_address(&[rewards::MINT_AUTHORITY_SEED], program_id);
+    let token_account = spl_associated_token_account::get_associated_token_address(player, &mint);
+    let instruction = Instruction::new_with_bytes(
+        *program_id,
+        &TypingInstruction::ClaimAchievement { achievement }.try_to_vec().unwrap(),
+        vec![
+            AccountMeta::new(*player, true),
+            AccountMeta::new(player_pda, false),
+            AccountMeta::new(mint, false),
+            AccountMeta::new_readonly(mint_authority, false),
+            AccountMeta::new(token_account, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+        ],
+    );
+    (mint, token_account, instruction)
+}
+
+fn custom_error(err: TypingError) -> TransactionError {
+    TransactionError::InstructionError(0, InstructionError::Custom(err as u32))
+}
+
+#[tokio::test]
+async fn test_claim_achievement_mints_a_single_token() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = ProgramTest::new("typing_master_program", program_id, processor!(process_instruction));
+    
+    let player = Keypair::new();
+    let (player_pda, _) = Pubkey::find_program_address(&[Player::SEED, player.pubkey().as_ref()], &program_id);
+    let mut player_data = Player::new(player.pubkey(), 0);
+    player_data.best_wpm = 120;
+    player_data.unlock_achievements();
+    program_test.add_account(
+        player.pubkey(),
+        Account {
+            lamports: 1_000_000_000,
+            owner: system_program::id(),
+            ..Account::default()
+        },
+    );
+    program_test.add_account(
+        player_pda,
+        Account {
+            lamports: 1_000_000_000,
+            data: player_data.try_to_vec().unwrap(),
+            owner: program_id,
+            ..Account::default()
+        },
+    );
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+    
+    let (mint, token_account, instruction) = claim_instruction(&program_id, &player.pubkey(), 0);
+    let transaction = Transaction::new_signed_with_payer(
+        core::slice::from_ref(&instruction),
+        Some(&payer.pubkey()),
+        &[&payer, &player],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+    
+    let mint_data = Mint::unpack(&banks_client.get_account(mint).await.unwrap().unwrap().data).unwrap();
+    assert_eq!(mint_data.supply, 1);
+    assert_eq!(mint_data.decimals, 0);
+    assert_eq!(mint_data.mint_authority, COption::None);
+    let token_data = TokenAccount::unpack(&banks_client.get_account(token_account).await.unwrap().unwrap().data).unwrap();
+    assert_eq!(token_data.owner, player.pubkey());
+    assert_eq!(token_data.amount, 1);
+    
+    let stored = banks_client.get_account(player_pda).await.unwrap().unwrap();
+    assert_eq!(Player::try_from_slice(&stored.data).unwrap().claimed_achievements, 1);
+    
+    // A second claim is rejected before touching the mint
+    let blockhash = banks_client.get_new_latest_blockhash(&recent_blockhash).await.unwrap();
+    let transaction =
+        Transaction::new_signed_with_payer(&[instruction], Some(&payer.pubkey()), &[&payer, &player], blockhash);
+    let err = banks_client.process_transaction(transaction).await.unwrap_err().unwrap();
+    assert_eq!(err, custom_error(TypingError::AchievementAlreadyClaimed));
+    
+    let (_, _, locked) = claim_instruction(&program_id, &player.pubkey(), 1);
+    let transaction = Transaction::new_signed_with_payer(&[locked], Some(&payer.pubkey()), &[&payer, &player], blockhash);
+    let err = banks_client.process_transaction(transaction).await.unwrap_err().unwrap();
+    assert_eq!(err, custom_error(TypingError::AchievementLocked));
+}
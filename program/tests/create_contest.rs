@@ -0,0 +1,115 @@
+//! Runs against `solana-program-test`, which installs process-wide syscall
+//! stubs, so it lives apart from the unit tests in `processor.rs`. Contest
+//! creation goes through the real system program's `create_account`.
+
+use borsh::BorshSerialize;
+use solana_program::{
+    instruction::{AccountMeta, Instruction, InstructionError},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    system_program,
+};
+use solana_program_test::{processor, tokio, BanksClient, ProgramTest, ProgramTestBanksClientExt};
+use solana_sdk::{
+    account::Account,
+    hash::Hash,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use typing_master_program::{
+    error::TypingError,
+    instruction::TypingInstruction,
+    process_instruction,
+    state::{ContestMode, ScoringRule, TextRegistry},
+};
+
+async fn start(program_id: Pubkey, registry: TextRegistry) -> (BanksClient, Keypair, Hash) {
+    let mut program_test = ProgramTest::new("typing_master_program", program_id, processor!(process_instruction));
+    let (registry_pda, _) = Pubkey::find_program_address(&[TextRegistry::SEED], &program_id);
+    let mut data = registry.try_to_vec().unwrap();
+    data.resize(TextRegistry::SIZE, 0);
+    program_test.add_account(
+        registry_pda,
+        Account {
+            lamports: 1_000_000_000,
+            data,
+            owner: program_id,
+            ..Account::default()
+        },
+    );
+    program_test.start().await
+}
+
+fn create_contest(
+    program_id: Pubkey,
+    payer: &Keypair,
+    contest: &Keypair,
+    text_id: Option<u32>,
+    with_registry: bool,
+) -> Instruction {
+    let data = TypingInstruction::CreateContest {
+        text_id,
+        duration: 120,
+        mode: ContestMode::FixedText,
+        scoring: ScoringRule::GrossWpm,
+        schedule: None,
+    }
+    .try_to_vec()
+    .unwrap();
+    let mut accounts = vec![
+        AccountMeta::new(payer.pubkey(), true),
+        AccountMeta::new_readonly(payer.pubkey(), true),
+        AccountMeta::new(contest.pubkey(), true),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    if with_registry {
+        let (registry_pda, _) = Pubkey::find_program_address(&[TextRegistry::SEED], &program_id);
+        accounts.push(AccountMeta::new_readonly(registry_pda, false));
+    }
+    Instruction::new_with_bytes(program_id, &data, accounts)
+}
+
+#[tokio::test]
+async fn test_create_contest_rejects_draw_from_empty_registry() {
+    let program_id = Pubkey::new_unique();
+    let (mut banks_client, payer, recent_blockhash) = start(program_id, TextRegistry::default()).await;
+    
+    // Drawing needs the registry, and the registry needs a text to draw
+    let cases = [
+        (false, InstructionError::from(u64::from(ProgramError::NotEnoughAccountKeys))),
+        (true, InstructionError::Custom(TypingError::TextNotRegistered as u32)),
+    ];
+    let mut blockhash = recent_blockhash;
+    for (with_registry, expected) in cases {
+        let contest = Keypair::new();
+        let instruction = create_contest(program_id, &payer, &contest, None, with_registry);
+        let transaction =
+            Transaction::new_signed_with_payer(&[instruction], Some(&payer.pubkey()), &[&payer, &contest], blockhash);
+        let err = banks_client.process_transaction(transaction).await.unwrap_err().unwrap();
+        assert_eq!(err, TransactionError::InstructionError(0, expected));
+        blockhash = banks_client.get_new_latest_blockhash(&blockhash).await.unwrap();
+    }
+    
+    // A fixed text doesn't touch the registry
+    let contest = Keypair::new();
+    let instruction = create_contest(program_id, &payer, &contest, Some(7), false);
+    let transaction =
+        Transaction::new_signed_with_payer(&[instruction], Some(&payer.pubkey()), &[&payer, &contest], blockhash);
+    banks_client.process_transaction(transaction).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_create_contest_draws_from_registered_texts() {
+    let program_id = Pubkey::new_unique();
+    let registry = TextRegistry { texts: vec![5] };
+    let (mut banks_client, payer, recent_blockhash) = start(program_id, registry).await;
+    
+    let contest = Keypair::new();
+    let instruction = create_contest(program_id, &payer, &contest, None, true);
+    let transaction =
+        Transaction::new_signed_with_payer(&[instruction], Some(&payer.pubkey()), &[&payer, &contest], recent_blockhash);
+    banks_client.process_transaction(transaction).await.unwrap();
+    
+    let stored = banks_client.get_account(contest.pubkey()).await.unwrap().unwrap();
+    assert_eq!(stored.owner, program_id);
+}
@@ -0,0 +1,120 @@
+//! Runs against `solana-program-test`, which installs process-wide syscall
+//! stubs, so it lives apart from the unit tests in `processor.rs`. The draw
+//! reads the real `SlotHashes` sysvar at a later slot than the start.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    clock::Clock,
+    hash::Hash,
+    instruction::{AccountMeta, Instruction, InstructionError},
+    pubkey::Pubkey,
+    slot_hashes::SlotHashes,
+    sysvar,
+};
+use solana_program_test::{processor, tokio, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::Account,
+    signature::Signer,
+    transaction::{Transaction, TransactionError},
+};
+use typing_master_program::{
+    error::TypingError,
+    instruction::TypingInstruction,
+    process_instruction,
+    state::{Contest, ContestMode, ScoringRule, TextRegistry},
+};
+
+fn started_contest(program_test: &mut ProgramTest, program_id: Pubkey, draw_slot: u64) -> Pubkey {
+    let key = Pubkey::new_unique();
+    let mut contest = Contest::new(Pubkey::new_unique(), None, 60, ContestMode::FixedText, ScoringRule::GrossWpm, None, 0);
+    contest.add_participant(Pubkey::new_unique()).unwrap();
+    contest.add_participant(Pubkey::new_unique()).unwrap();
+    contest.start_contest(0);
+    contest.commit_draw(draw_slot);
+    let mut data = contest.try_to_vec().unwrap();
+    data.resize(Contest::SIZE, 0);
+    program_test.add_account(
+        key,
+        Account {
+            lamports: 1_000_000_000,
+            data,
+            owner: program_id,
+            ..Account::default()
+        },
+    );
+    key
+}
+
+async fn draw(context: &mut ProgramTestContext, program_id: Pubkey, contest: Pubkey) -> Result<Contest, TransactionError> {
+    let (registry_pda, _) = Pubkey::find_program_address(&[TextRegistry::SEED], &program_id);
+    let instruction = Instruction::new_with_bytes(
+        program_id,
+        &TypingInstruction::DrawContestText.try_to_vec().unwrap(),
+        vec![
+            AccountMeta::new(contest, false),
+            AccountMeta::new_readonly(registry_pda, false),
+            AccountMeta::new_readonly(sysvar::slot_hashes::id(), false),
+        ],
+    );
+    let blockhash = context.get_new_latest_blockhash().await.unwrap();
+    let transaction =
+        Transaction::new_signed_with_payer(&[instruction], Some(&context.payer.pubkey()), &[&context.payer], blockhash);
+    context.banks_client.process_transaction(transaction).await.map_err(|err| err.unwrap())?;
+    let stored = context.banks_client.get_account(contest).await.unwrap().unwrap();
+    Ok(Contest::deserialize(&mut &stored.data[..]).unwrap())
+}
+
+#[tokio::test]
+async fn test_draw_uses_the_hash_of_the_start_slot() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = ProgramTest::new("typing_master_program", program_id, processor!(process_instruction));
+    
+    let registry = TextRegistry { texts: vec![3, 5, 8, 13] };
+    let (registry_pda, _) = Pubkey::find_program_address(&[TextRegistry::SEED], &program_id);
+    let mut data = registry.try_to_vec().unwrap();
+    data.resize(TextRegistry::SIZE, 0);
+    program_test.add_account(
+        registry_pda,
+        Account {
+            lamports: 1_000_000_000,
+            data,
+            owner: program_id,
+            ..Account::default()
+        },
+    );
+    let start_slot = 100;
+    let drawn = started_contest(&mut program_test, program_id, start_slot);
+    let stale = started_contest(&mut program_test, program_id, start_slot);
+    let mut context = program_test.start_with_context().await;
+    
+    // Until the start slot has passed its hash isn't known
+    context.warp_to_slot(start_slot).unwrap();
+    assert_eq!(
+        draw(&mut context, program_id, drawn).await.unwrap_err(),
+        TransactionError::InstructionError(0, InstructionError::Custom(TypingError::DrawNotReady as u32))
+    );
+    
+    let start_hash = Hash::new_from_array([7; 32]);
+    context.warp_to_slot(start_slot + 2).unwrap();
+    context.set_sysvar(&SlotHashes::new(&[
+        (start_slot + 1, Hash::new_from_array([9; 32])),
+        (start_slot, start_hash),
+    ]));
+    let now = context.banks_client.get_sysvar::<Clock>().await.unwrap().unix_timestamp;
+    let contest = draw(&mut context, program_id, drawn).await.unwrap();
+    let expected = registry.pick(&[drawn.as_ref(), start_hash.as_ref()]).unwrap();
+    assert_eq!(contest.text_id, Some(expected));
+    // The race clock starts with the draw
+    assert!(contest.started_at.is_some_and(|started_at| started_at >= now));
+    assert_eq!(
+        draw(&mut context, program_id, drawn).await.unwrap_err(),
+        TransactionError::InstructionError(0, InstructionError::Custom(TypingError::ContestAlreadyStarted as u32))
+    );
+    
+    // Once the start slot has aged out the contest commits to a new one
+    let later = start_slot + 600;
+    context.warp_to_slot(later).unwrap();
+    context.set_sysvar(&SlotHashes::new(&[(later - 1, Hash::new_from_array([9; 32]))]));
+    let contest = draw(&mut context, program_id, stale).await.unwrap();
+    assert_eq!((contest.text_id, contest.draw_slot), (None, Some(later)));
+}
@@ -0,0 +1,65 @@
+//! Runs against `solana-program-test`, which installs process-wide syscall
+//! stubs, so it lives apart from the unit tests in `processor.rs`. The
+//! pre-funded path goes through the real system program's transfer,
+//! allocate and assign.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    rent::Rent,
+    system_program,
+};
+use solana_program_test::{processor, tokio, ProgramTest};
+use solana_sdk::{
+    account::Account,
+    signature::{Keypair, Signer},
+    transaction::Transaction,
+};
+use typing_master_program::{instruction::TypingInstruction, process_instruction, state::Player};
+
+#[tokio::test]
+async fn test_initialize_player_with_prefunded_pda() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = ProgramTest::new("typing_master_program", program_id, processor!(process_instruction));
+    
+    let rent = Rent::default().minimum_balance(Player::SIZE);
+    // One PDA was sent less than the rent, the other more, before either
+    // player registered
+    let players = [(Keypair::new(), 1_000), (Keypair::new(), rent + 5_000)];
+    for (player, prefunded) in &players {
+        let (player_pda, _) = Pubkey::find_program_address(&[Player::SEED, player.pubkey().as_ref()], &program_id);
+        program_test.add_account(
+            player_pda,
+            Account {
+                lamports: *prefunded,
+                owner: system_program::id(),
+                ..Account::default()
+            },
+        );
+    }
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+    
+    for (player, prefunded) in &players {
+        let (player_pda, _) = Pubkey::find_program_address(&[Player::SEED, player.pubkey().as_ref()], &program_id);
+        let instruction = Instruction::new_with_bytes(
+            program_id,
+            &TypingInstruction::InitializePlayer.try_to_vec().unwrap(),
+            vec![
+                AccountMeta::new(payer.pubkey(), true),
+                AccountMeta::new_readonly(player.pubkey(), true),
+                AccountMeta::new(player_pda, false),
+                AccountMeta::new_readonly(system_program::id(), false),
+            ],
+        );
+        let transaction =
+            Transaction::new_signed_with_payer(&[instruction], Some(&payer.pubkey()), &[&payer, player], recent_blockhash);
+        banks_client.process_transaction(transaction).await.unwrap();
+        
+        let stored = banks_client.get_account(player_pda).await.unwrap().unwrap();
+        assert_eq!(stored.owner, program_id);
+        assert_eq!(stored.data.len(), Player::SIZE);
+        assert_eq!(stored.lamports, rent.max(*prefunded));
+        assert_eq!(Player::try_from_slice(&stored.data).unwrap().owner, player.pubkey());
+    }
+}
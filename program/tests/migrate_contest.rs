@@ -0,0 +1,102 @@
+//! Runs against `solana-program-test`, which installs process-wide syscall
+//! stubs, so it lives apart from the unit tests in `processor.rs`. `realloc`
+//! also needs real runtime account memory.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    instruction::{AccountMeta, Instruction, InstructionError},
+    pubkey::Pubkey,
+    rent::Rent,
+    system_program,
+};
+use solana_program_test::{processor, tokio, ProgramTest, ProgramTestBanksClientExt};
+use solana_sdk::{
+    account::Account,
+    signature::Signer,
+    transaction::{Transaction, TransactionError},
+};
+use typing_master_program::{
+    error::TypingError,
+    instruction::TypingInstruction,
+    process_instruction,
+    state::{Contest, ContestMode, ContestStatus, LegacyContest, LegacyContestResult, ScoringRule},
+};
+
+#[tokio::test]
+async fn test_migrate_contest_grows_legacy_account() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = ProgramTest::new("typing_master_program", program_id, processor!(process_instruction));
+    
+    let contest_key = Pubkey::new_unique();
+    let (first, second) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let legacy = LegacyContest {
+        creator: first,
+        text_id: 3,
+        duration: 120,
+        status: ContestStatus::Ended,
+        participants: vec![first, second],
+        results: vec![LegacyContestResult {
+            player: second,
+            wpm: 80,
+            accuracy: 97,
+            time_taken: 90,
+            position: 1,
+        }],
+        created_at: 1_000,
+        started_at: Some(1_100),
+        ended_at: Some(1_300),
+        max_participants: 10,
+    };
+    let mut data = legacy.try_to_vec().unwrap();
+    data.resize(LegacyContest::SIZE, 0);
+    program_test.add_account(
+        contest_key,
+        Account {
+            lamports: Rent::default().minimum_balance(LegacyContest::SIZE),
+            data,
+            owner: program_id,
+            ..Account::default()
+        },
+    );
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+    
+    let migrate = Instruction::new_with_bytes(
+        program_id,
+        &TypingInstruction::MigrateContest.try_to_vec().unwrap(),
+        vec![
+            AccountMeta::new(payer.pubkey(), true),
+            AccountMeta::new(contest_key, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    );
+    let transaction = Transaction::new_signed_with_payer(
+        core::slice::from_ref(&migrate),
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+    
+    let stored = banks_client.get_account(contest_key).await.unwrap().unwrap();
+    assert_eq!(stored.data.len(), Contest::SIZE);
+    assert!(stored.lamports >= Rent::default().minimum_balance(Contest::SIZE));
+    let contest = Contest::deserialize(&mut &stored.data[..]).unwrap();
+    assert_eq!(contest.version, Contest::VERSION);
+    assert_eq!((contest.creator, contest.text_id, contest.duration), (first, Some(3), 120));
+    assert_eq!((contest.mode, contest.scoring), (ContestMode::FixedText, ScoringRule::GrossWpm));
+    assert_eq!(contest.status, ContestStatus::Ended);
+    assert_eq!(contest.participants, vec![first, second]);
+    assert_eq!(contest.progress.len(), 2);
+    assert_eq!((contest.results[0].player, contest.results[0].score), (second, 8_000));
+    assert_eq!((contest.started_at, contest.ended_at), (Some(1_100), Some(1_300)));
+    assert_eq!(contest.outcomes_recorded, 0);
+    
+    // Migrating again is rejected rather than reading the new layout as old
+    let blockhash = banks_client.get_new_latest_blockhash(&recent_blockhash).await.unwrap();
+    let transaction = Transaction::new_signed_with_payer(&[migrate], Some(&payer.pubkey()), &[&payer], blockhash);
+    let err = banks_client.process_transaction(transaction).await.unwrap_err().unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(0, InstructionError::Custom(TypingError::ContestAlreadyMigrated as u32))
+    );
+}
@@ -0,0 +1,89 @@
+//! Runs against `solana-program-test`, which installs process-wide syscall
+//! stubs, so it lives apart from the unit tests in `processor.rs`. `realloc`
+//! also needs real runtime account memory.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    instruction::{AccountMeta, Instruction, InstructionError},
+    pubkey::Pubkey,
+    rent::Rent,
+    system_program,
+};
+use solana_program_test::{processor, tokio, ProgramTest, ProgramTestBanksClientExt};
+use solana_sdk::{
+    account::Account,
+    signature::Signer,
+    transaction::{Transaction, TransactionError},
+};
+use typing_master_program::{
+    error::TypingError,
+    instruction::TypingInstruction,
+    process_instruction,
+    state::{LegacyPlayer, Player},
+};
+
+#[tokio::test]
+async fn test_migrate_player_grows_legacy_account() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = ProgramTest::new("typing_master_program", program_id, processor!(process_instruction));
+    
+    let owner = Pubkey::new_unique();
+    let (player_pda, _) = Pubkey::find_program_address(&[Player::SEED, owner.as_ref()], &program_id);
+    let legacy = LegacyPlayer {
+        owner,
+        total_tests: 4,
+        best_wpm: 90,
+        average_wpm: 75,
+        best_accuracy: 98,
+        total_words_typed: 600,
+        created_at: 1_000,
+        last_activity: 500_000,
+    };
+    program_test.add_account(
+        player_pda,
+        Account {
+            lamports: Rent::default().minimum_balance(LegacyPlayer::SIZE),
+            data: legacy.try_to_vec().unwrap(),
+            owner: program_id,
+            ..Account::default()
+        },
+    );
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+    
+    let migrate = Instruction::new_with_bytes(
+        program_id,
+        &TypingInstruction::MigratePlayer.try_to_vec().unwrap(),
+        vec![
+            AccountMeta::new(payer.pubkey(), true),
+            AccountMeta::new(player_pda, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    );
+    let transaction = Transaction::new_signed_with_payer(
+        core::slice::from_ref(&migrate),
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+    
+    let stored = banks_client.get_account(player_pda).await.unwrap().unwrap();
+    assert_eq!(stored.data.len(), Player::SIZE);
+    assert!(stored.lamports >= Rent::default().minimum_balance(Player::SIZE));
+    let player = Player::try_from_slice(&stored.data).unwrap();
+    assert_eq!(player.version, Player::VERSION);
+    assert_eq!(player.owner, owner);
+    assert_eq!((player.total_tests, player.best_wpm, player.average_wpm), (4, 90, 75));
+    assert_eq!(player.wpm_sum, 300);
+    assert_eq!(player.total_words_typed, 600);
+    assert_eq!((player.created_at, player.last_activity), (1_000, 500_000));
+    
+    // Migrating again is rejected rather than reading the new layout as old
+    let blockhash = banks_client.get_new_latest_blockhash(&recent_blockhash).await.unwrap();
+    let transaction = Transaction::new_signed_with_payer(&[migrate], Some(&payer.pubkey()), &[&payer], blockhash);
+    let err = banks_client.process_transaction(transaction).await.unwrap_err().unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(0, InstructionError::Custom(TypingError::PlayerAlreadyMigrated as u32))
+    );
+}
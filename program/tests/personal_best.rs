@@ -0,0 +1,171 @@
+//! Runs against `solana-program-test`, which installs process-wide syscall
+//! stubs, so it lives apart from the unit tests in `processor.rs`. Repeat
+//! runs read the personal best back from real account data.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    instruction::{AccountMeta, Instruction, InstructionError},
+    pubkey::Pubkey,
+    system_program,
+};
+use solana_program_test::{processor, tokio, ProgramTest, ProgramTestBanksClientExt};
+use solana_sdk::{
+    account::Account,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use typing_master_program::{
+    error::TypingError,
+    instruction::TypingInstruction,
+    process_instruction,
+    state::{Config, ConfigParams, Ghost, PersonalBest, Player, PlayerHistory, TextRecord, TypingStats},
+};
+
+#[tokio::test]
+async fn test_repeat_runs_and_publish_committed_ghost() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = ProgramTest::new("typing_master_program", program_id, processor!(process_instruction));
+    
+    let player = Keypair::new();
+    let text_id: u32 = 4;
+    let (config_pda, _) = Pubkey::find_program_address(&[Config::SEED], &program_id);
+    let (player_pda, _) = Pubkey::find_program_address(&[Player::SEED, player.pubkey().as_ref()], &program_id);
+    let (personal_best_pda, _) = Pubkey::find_program_address(
+        &[PersonalBest::SEED, player.pubkey().as_ref(), &text_id.to_le_bytes()],
+        &program_id,
+    );
+    let (text_record_pda, _) = Pubkey::find_program_address(&[TextRecord::SEED, &text_id.to_le_bytes()], &program_id);
+    let (history_pda, _) = Pubkey::find_program_address(&[PlayerHistory::SEED, player.pubkey().as_ref()], &program_id);
+    let (ghost_pda, _) = Pubkey::find_program_address(
+        &[Ghost::SEED, player.pubkey().as_ref(), &text_id.to_le_bytes()],
+        &program_id,
+    );
+    
+    let config = Config::new(
+        Pubkey::new_unique(),
+        ConfigParams {
+            min_practice_interval: 0,
+            max_daily_practice_sessions: 0,
+            xp_daily_player_cap: 0,
+            xp_daily_global_cap: 0,
+        },
+    );
+    program_test.add_account(
+        player.pubkey(),
+        Account {
+            lamports: 1_000_000_000,
+            owner: system_program::id(),
+            ..Account::default()
+        },
+    );
+    program_test.add_account(
+        config_pda,
+        Account {
+            lamports: 1_000_000_000,
+            data: config.try_to_vec().unwrap(),
+            owner: program_id,
+            ..Account::default()
+        },
+    );
+    program_test.add_account(
+        player_pda,
+        Account {
+            lamports: 1_000_000_000,
+            data: Player::new(player.pubkey(), 0).try_to_vec().unwrap(),
+            owner: program_id,
+            ..Account::default()
+        },
+    );
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+    
+    // 60 WPM is 5 chars per second: one sample per second for a minute
+    let samples: Vec<u16> = (1..=60).map(|second| second * 5).collect();
+    let trace_hash = Ghost::trace_hash(1000, &samples);
+    let practice = |correct_chars: u32, trace_hash: Option<[u8; 32]>| {
+        let data = TypingInstruction::UpdatePracticeStats {
+            stats: TypingStats {
+                correct_chars,
+                incorrect_chars: 0,
+                corrected_errors: 0,
+                total_keystrokes: correct_chars,
+                elapsed_ms: 60_000,
+            },
+            text_id,
+            key_deltas: vec![],
+            trace_hash,
+        }
+        .try_to_vec()
+        .unwrap();
+        Instruction::new_with_bytes(
+            program_id,
+            &data,
+            vec![
+                AccountMeta::new(player.pubkey(), true),
+                AccountMeta::new(player_pda, false),
+                AccountMeta::new(personal_best_pda, false),
+                AccountMeta::new(text_record_pda, false),
+                AccountMeta::new(history_pda, false),
+                AccountMeta::new_readonly(config_pda, false),
+                AccountMeta::new_readonly(system_program::id(), false),
+            ],
+        )
+    };
+    let publish = |samples: Vec<u16>| {
+        let data = TypingInstruction::PublishGhost {
+            text_id,
+            interval_ms: 1000,
+            samples,
+        }
+        .try_to_vec()
+        .unwrap();
+        Instruction::new_with_bytes(
+            program_id,
+            &data,
+            vec![
+                AccountMeta::new(player.pubkey(), true),
+                AccountMeta::new_readonly(player_pda, false),
+                AccountMeta::new_readonly(personal_best_pda, false),
+                AccountMeta::new(ghost_pda, false),
+                AccountMeta::new_readonly(system_program::id(), false),
+            ],
+        )
+    };
+    
+    // The second run on the same text reads back the account the first
+    // one created, and commits its trace as the new best
+    let transaction = Transaction::new_signed_with_payer(
+        &[practice(250, None), practice(300, Some(trace_hash))],
+        Some(&payer.pubkey()),
+        &[&payer, &player],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+    
+    let stored = banks_client.get_account(personal_best_pda).await.unwrap().unwrap();
+    assert_eq!(stored.data.len(), PersonalBest::SIZE);
+    let personal_best = PersonalBest::try_from_slice(&stored.data).unwrap();
+    assert_eq!((personal_best.attempts, personal_best.best_wpm), (2, 60));
+    assert_eq!((personal_best.has_ghost, personal_best.ghost_hash), (true, trace_hash));
+    
+    // A fabricated trace at the same pace doesn't match the commitment
+    let mut fabricated = samples.clone();
+    fabricated[0] = 4;
+    let blockhash = banks_client.get_new_latest_blockhash(&recent_blockhash).await.unwrap();
+    let transaction =
+        Transaction::new_signed_with_payer(&[publish(fabricated)], Some(&payer.pubkey()), &[&payer, &player], blockhash);
+    let err = banks_client.process_transaction(transaction).await.unwrap_err().unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(0, InstructionError::Custom(TypingError::GhostNotCommitted as u32))
+    );
+    
+    let blockhash = banks_client.get_new_latest_blockhash(&blockhash).await.unwrap();
+    let transaction =
+        Transaction::new_signed_with_payer(&[publish(samples.clone())], Some(&payer.pubkey()), &[&payer, &player], blockhash);
+    banks_client.process_transaction(transaction).await.unwrap();
+    
+    let stored = banks_client.get_account(ghost_pda).await.unwrap().unwrap();
+    let ghost = Ghost::deserialize(&mut &stored.data[..]).unwrap();
+    assert_eq!((ghost.wpm, ghost.trace_hash), (60, trace_hash));
+    assert_eq!(ghost.samples, samples);
+}
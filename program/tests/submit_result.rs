@@ -0,0 +1,139 @@
+//! Runs against `solana-program-test`, which installs process-wide syscall
+//! stubs, so it lives apart from the unit tests in `processor.rs`. The race
+//! window is checked against the runtime's clock.
+
+use borsh::BorshSerialize;
+use solana_program::{
+    clock::Clock,
+    instruction::{AccountMeta, Instruction, InstructionError},
+    pubkey::Pubkey,
+    system_program,
+};
+use solana_program_test::{processor, tokio, ProgramTest};
+use solana_sdk::{
+    account::Account,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use typing_master_program::{
+    error::TypingError,
+    instruction::TypingInstruction,
+    process_instruction,
+    state::{Contest, ContestMode, PersonalBest, Player, PlayerHistory, ScoringRule, TextRecord, TypingStats},
+};
+
+#[tokio::test]
+async fn test_submit_result_rejects_runs_after_the_window() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = ProgramTest::new("typing_master_program", program_id, processor!(process_instruction));
+    
+    let player = Keypair::new();
+    let text_id: u32 = 2;
+    let (player_pda, _) = Pubkey::find_program_address(&[Player::SEED, player.pubkey().as_ref()], &program_id);
+    let (personal_best_pda, _) = Pubkey::find_program_address(
+        &[PersonalBest::SEED, player.pubkey().as_ref(), &text_id.to_le_bytes()],
+        &program_id,
+    );
+    let (text_record_pda, _) = Pubkey::find_program_address(&[TextRecord::SEED, &text_id.to_le_bytes()], &program_id);
+    let (history_pda, _) = Pubkey::find_program_address(&[PlayerHistory::SEED, player.pubkey().as_ref()], &program_id);
+    
+    program_test.add_account(
+        player.pubkey(),
+        Account {
+            lamports: 1_000_000_000,
+            owner: system_program::id(),
+            ..Account::default()
+        },
+    );
+    program_test.add_account(
+        player_pda,
+        Account {
+            lamports: 1_000_000_000,
+            data: Player::new(player.pubkey(), 0).try_to_vec().unwrap(),
+            owner: program_id,
+            ..Account::default()
+        },
+    );
+    let mut context = program_test.start_with_context().await;
+    let now = context.banks_client.get_sysvar::<Clock>().await.unwrap().unix_timestamp;
+    
+    // One race closed a second ago, the other has most of an hour to go
+    let mut add_contest = |started_at: i64, duration: u64| {
+        let key = Pubkey::new_unique();
+        let mut contest = Contest::new(
+            Pubkey::new_unique(),
+            Some(text_id),
+            duration,
+            ContestMode::FixedText,
+            ScoringRule::GrossWpm,
+            None,
+            started_at,
+        );
+        contest.add_participant(player.pubkey()).unwrap();
+        contest.add_participant(Pubkey::new_unique()).unwrap();
+        contest.start_contest(started_at);
+        let mut data = contest.try_to_vec().unwrap();
+        data.resize(Contest::SIZE, 0);
+        context.set_account(
+            &key,
+            &Account {
+                lamports: 1_000_000_000,
+                data,
+                owner: program_id,
+                ..Account::default()
+            }
+            .into(),
+        );
+        key
+    };
+    let expired = add_contest(now - 121, 120);
+    let running = add_contest(now, 3_600);
+    
+    let submit = |contest: Pubkey| {
+        let data = TypingInstruction::SubmitResult {
+            stats: TypingStats {
+                correct_chars: 300,
+                incorrect_chars: 0,
+                corrected_errors: 0,
+                total_keystrokes: 300,
+                elapsed_ms: 60_000,
+            },
+            trace_hash: None,
+        }
+        .try_to_vec()
+        .unwrap();
+        Instruction::new_with_bytes(
+            program_id,
+            &data,
+            vec![
+                AccountMeta::new(player.pubkey(), true),
+                AccountMeta::new(contest, false),
+                AccountMeta::new(player_pda, false),
+                AccountMeta::new(personal_best_pda, false),
+                AccountMeta::new(text_record_pda, false),
+                AccountMeta::new(history_pda, false),
+                AccountMeta::new_readonly(system_program::id(), false),
+            ],
+        )
+    };
+    
+    let transaction = Transaction::new_signed_with_payer(
+        &[submit(expired)],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &player],
+        context.last_blockhash,
+    );
+    let err = context.banks_client.process_transaction(transaction).await.unwrap_err().unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(0, InstructionError::Custom(TypingError::ContestNotActive as u32))
+    );
+    
+    let transaction = Transaction::new_signed_with_payer(
+        &[submit(running)],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &player],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(transaction).await.unwrap();
+}
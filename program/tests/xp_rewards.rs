@@ -0,0 +1,158 @@
+//! Runs against `solana-program-test`, which installs process-wide syscall
+//! stubs, so it lives apart from the unit tests in `processor.rs`.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program_pack::Pack,
+    pubkey::Pubkey,
+    system_program,
+};
+use solana_program_test::{processor, tokio, ProgramTest};
+use solana_sdk::{
+    account::Account,
+    signature::{Keypair, Signer},
+    transaction::Transaction,
+};
+use spl_associated_token_account::{
+    get_associated_token_address, instruction::create_associated_token_account_idempotent,
+};
+use spl_token::state::{Account as TokenAccount, Mint};
+use typing_master_program::{
+    instruction::TypingInstruction,
+    process_instruction, rewards,
+    state::{Config, ConfigParams, PersonalBest, Player, PlayerHistory, TextRecord, TypingStats},
+};
+
+#[tokio::test]
+async fn test_practice_mints_capped_xp() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = ProgramTest::new("typing_master_program", program_id, processor!(process_instruction));
+    
+    let admin = Keypair::new();
+    let player = Keypair::new();
+    let (config_pda, _) = Pubkey::find_program_address(&[Config::SEED], &program_id);
+    let (player_pda, _) = Pubkey::find_program_address(&[Player::SEED, player.pubkey().as_ref()], &program_id);
+    let (xp_mint, _) = Pubkey::find_program_address(&[rewards::XP_MINT_SEED], &program_id);
+    let (mint_authority, _) = Pubkey::find_program_address(&[rewards::MINT_AUTHORITY_SEED], &program_id);
+    let xp_token_account = get_associated_token_address(&player.pubkey(), &xp_mint);
+    
+    let config = Config::new(
+        admin.pubkey(),
+        ConfigParams {
+            min_practice_interval: 0,
+            max_daily_practice_sessions: 0,
+            xp_daily_player_cap: 60,
+            xp_daily_global_cap: 1_000,
+        },
+    );
+    for wallet in [admin.pubkey(), player.pubkey()] {
+        program_test.add_account(
+            wallet,
+            Account {
+                lamports: 1_000_000_000,
+                owner: system_program::id(),
+                ..Account::default()
+            },
+        );
+    }
+    program_test.add_account(
+        config_pda,
+        Account {
+            lamports: 1_000_000_000,
+            data: config.try_to_vec().unwrap(),
+            owner: program_id,
+            ..Account::default()
+        },
+    );
+    program_test.add_account(
+        player_pda,
+        Account {
+            lamports: 1_000_000_000,
+            data: Player::new(player.pubkey(), 0).try_to_vec().unwrap(),
+            owner: program_id,
+            ..Account::default()
+        },
+    );
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+    
+    let initialize_mint = Instruction::new_with_bytes(
+        program_id,
+        &TypingInstruction::InitializeXpMint.try_to_vec().unwrap(),
+        vec![
+            AccountMeta::new(admin.pubkey(), true),
+            AccountMeta::new_readonly(config_pda, false),
+            AccountMeta::new(xp_mint, false),
+            AccountMeta::new_readonly(mint_authority, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+    );
+    let create_token_account =
+        create_associated_token_account_idempotent(&player.pubkey(), &player.pubkey(), &xp_mint, &spl_token::id());
+    let practice = |text_id: u32, with_xp: bool| {
+        let (personal_best, _) = Pubkey::find_program_address(
+            &[PersonalBest::SEED, player.pubkey().as_ref(), &text_id.to_le_bytes()],
+            &program_id,
+        );
+        let (text_record, _) = Pubkey::find_program_address(&[TextRecord::SEED, &text_id.to_le_bytes()], &program_id);
+        let (history, _) = Pubkey::find_program_address(&[PlayerHistory::SEED, player.pubkey().as_ref()], &program_id);
+        let data = TypingInstruction::UpdatePracticeStats {
+            stats: TypingStats {
+                correct_chars: 270,
+                incorrect_chars: 20,
+                corrected_errors: 10,
+                total_keystrokes: 320,
+                elapsed_ms: 40_000,
+            },
+            text_id,
+            key_deltas: vec![],
+            trace_hash: None,
+        }
+        .try_to_vec()
+        .unwrap();
+        let mut accounts = vec![
+            AccountMeta::new(player.pubkey(), true),
+            AccountMeta::new(player_pda, false),
+            AccountMeta::new(personal_best, false),
+            AccountMeta::new(text_record, false),
+            AccountMeta::new(history, false),
+            AccountMeta {
+                pubkey: config_pda,
+                is_signer: false,
+                is_writable: with_xp,
+            },
+            AccountMeta::new_readonly(system_program::id(), false),
+        ];
+        if with_xp {
+            accounts.extend([
+                AccountMeta::new(xp_mint, false),
+                AccountMeta::new_readonly(mint_authority, false),
+                AccountMeta::new(xp_token_account, false),
+                AccountMeta::new_readonly(spl_token::id(), false),
+            ]);
+        }
+        Instruction::new_with_bytes(program_id, &data, accounts)
+    };
+    
+    let transaction = Transaction::new_signed_with_payer(
+        // Practice works before the XP mint exists; it just earns nothing
+        &[practice(3, false), initialize_mint, create_token_account, practice(1, true), practice(2, true)],
+        Some(&payer.pubkey()),
+        &[&payer, &admin, &player],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+    
+    // 54 words at 90% is 48 XP per session, cut to the player's daily cap of 60
+    let mint_data = Mint::unpack(&banks_client.get_account(xp_mint).await.unwrap().unwrap().data).unwrap();
+    assert_eq!(mint_data.decimals, 0);
+    assert_eq!(mint_data.supply, 60);
+    let token_data =
+        TokenAccount::unpack(&banks_client.get_account(xp_token_account).await.unwrap().unwrap().data).unwrap();
+    assert_eq!(token_data.amount, 60);
+    let stored = banks_client.get_account(player_pda).await.unwrap().unwrap();
+    assert_eq!(Player::try_from_slice(&stored.data).unwrap().total_tests, 3);
+    let stored = banks_client.get_account(config_pda).await.unwrap().unwrap();
+    assert_eq!(Config::try_from_slice(&stored.data).unwrap().xp_tally.minted, 60);
+}